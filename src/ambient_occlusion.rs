@@ -0,0 +1,62 @@
+use crate::dither;
+use crate::vec3::*;
+use crate::{Game, RayHit};
+
+// Distance from an edge that occlusion reaches, in blocks.
+const AO_RADIUS: f32 = 0.4;
+// Shading of a fully occluded pixel, in palette colors.
+const AO_SHADE: f32 = 1.0;
+
+impl Game {
+    // Find how much a hit point should be darkened by the voxels around it,
+    // pixels close to a concave edge or corner are darker.
+    pub(crate) fn ambient_occlusion(&self, ray_hit: &RayHit) -> f32 {
+        // How close the hit point is to each edge of the face,
+        // 1.0 on the edge and 0.0 once it's AO_RADIUS away.
+        let near_min_u = 1.0 - ray_hit.u / AO_RADIUS;
+        let near_max_u = 1.0 - (1.0 - ray_hit.u) / AO_RADIUS;
+        let near_min_v = 1.0 - ray_hit.v / AO_RADIUS;
+        let near_max_v = 1.0 - (1.0 - ray_hit.v) / AO_RADIUS;
+        let near_u = near_min_u.max(near_max_u);
+        let near_v = near_min_v.max(near_max_v);
+
+        // Most pixels aren't near an edge, so skip looking up neighbors for them.
+        if near_u <= 0.0 && near_v <= 0.0 {
+            return 0.0;
+        }
+
+        // Only the voxels in front of the face can occlude it,
+        // step towards the edges that the hit point is closest to.
+        let normal = ray_hit.face.normal();
+        let (tangent_u, tangent_v) = ray_hit.face.tangents();
+        let step_u = if near_min_u > near_max_u { -1 } else { 1 };
+        let step_v = if near_min_v > near_max_v { -1 } else { 1 };
+        let is_occluder = |u: i32, v: i32| {
+            self.hit_map(&Vec3::new(
+                ray_hit.block.x + normal.x + tangent_u.x * u + tangent_v.x * v,
+                ray_hit.block.y + normal.y + tangent_u.y * u + tangent_v.y * v,
+                ray_hit.block.z + normal.z + tangent_u.z * u + tangent_v.z * v,
+            ))
+        };
+
+        let near_u = near_u.max(0.0);
+        let near_v = near_v.max(0.0);
+        let side_u = near_u > 0.0 && is_occluder(step_u, 0);
+        let side_v = near_v > 0.0 && is_occluder(0, step_v);
+
+        let mut occlusion = 0.0;
+        if side_u {
+            occlusion += near_u;
+        }
+        if side_v {
+            occlusion += near_v;
+        }
+        // A corner voxel only matters when neither of the sides next to it are occluding.
+        if !side_u && !side_v && near_u > 0.0 && near_v > 0.0 && is_occluder(step_u, step_v) {
+            occlusion = near_u.min(near_v);
+        }
+
+        let occlusion: f32 = occlusion.min(1.0);
+        (occlusion * AO_SHADE * dither::SHADE_BANDS).round() / dither::SHADE_BANDS
+    }
+}
@@ -0,0 +1,198 @@
+use crate::face::Face;
+use crate::light;
+use crate::texture;
+
+// Voxel ids stored in the map:
+pub const AIR: u8 = 0;
+pub const STONE: u8 = 1;
+pub const DIRT: u8 = 2;
+pub const GRASS: u8 = 3;
+pub const WOOD: u8 = 4;
+pub const PLANKS: u8 = 5;
+// Surrounds finite worlds, it doesn't need to be stored in the map.
+pub const BEDROCK: u8 = 6;
+pub const GLASS: u8 = 7;
+pub const LEAVES: u8 = 8;
+pub const WATER: u8 = 9;
+pub const MIRROR: u8 = 10;
+pub const TORCH: u8 = 11;
+pub const GLOWSTONE: u8 = 12;
+pub const LAVA: u8 = 13;
+
+pub const BLOCK_COUNT: usize = 14;
+
+// How much of what's behind a block can be seen through it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Opacity {
+    Opaque,
+    // Texels using palette color 0 are holes that rays pass through.
+    Cutout,
+    // Covers the given number of dither levels out of dither::LEVELS,
+    // rays pass through the rest of the pixels.
+    Translucent(u8),
+}
+
+// Cycles a face through consecutive tiles in the texture atlas,
+// starting with the face's own tile.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Animation {
+    pub frames: u8,
+    // Number of game frames that each tile is shown for.
+    pub frame_duration: u8,
+}
+
+impl Animation {
+    pub const NONE: Self = Self {
+        frames: 1,
+        frame_duration: 1,
+    };
+
+    // Get which frame of the animation is showing.
+    pub fn frame(&self, frame_count: u32) -> u8 {
+        (frame_count / self.frame_duration as u32 % self.frames as u32) as u8
+    }
+}
+
+pub struct Block {
+    // Rays used to break and place blocks are stopped by solid blocks.
+    pub solid: bool,
+    pub opacity: Opacity,
+    // Tiles in the texture atlas used by each face.
+    pub top_texture: u8,
+    pub bottom_texture: u8,
+    pub side_texture: u8,
+    // Darken faces depending on the axis they face,
+    // otherwise every face uses the texture's colors as-is.
+    pub shaded: bool,
+    // Number of hits needed to break the block, 0 means it can't be broken.
+    pub hardness: u8,
+    // Rays bounce off of texels using palette color 0.
+    pub reflective: bool,
+    // Light level given off by the block, 0 for blocks that don't glow.
+    pub emission: u8,
+    pub animation: Animation,
+}
+
+impl Block {
+    const fn new(texture: u8, hardness: u8) -> Self {
+        Self::with_faces(texture, texture, texture, hardness)
+    }
+
+    const fn with_faces(
+        top_texture: u8,
+        bottom_texture: u8,
+        side_texture: u8,
+        hardness: u8,
+    ) -> Self {
+        Self {
+            solid: true,
+            opacity: Opacity::Opaque,
+            top_texture,
+            bottom_texture,
+            side_texture,
+            shaded: true,
+            hardness,
+            reflective: false,
+            emission: 0,
+            animation: Animation::NONE,
+        }
+    }
+
+    const fn with_opacity(self, opacity: Opacity) -> Self {
+        Self { opacity, ..self }
+    }
+
+    const fn with_solid(self, solid: bool) -> Self {
+        Self { solid, ..self }
+    }
+
+    const fn with_reflective(self, reflective: bool) -> Self {
+        Self { reflective, ..self }
+    }
+
+    // Glowing blocks are drawn without shading, so they're bright even in the dark.
+    const fn with_emission(self, emission: u8) -> Self {
+        Self {
+            emission,
+            shaded: false,
+            ..self
+        }
+    }
+
+    const fn with_animation(self, frames: u8, frame_duration: u8) -> Self {
+        Self {
+            animation: Animation {
+                frames,
+                frame_duration,
+            },
+            ..self
+        }
+    }
+
+    // Get the tile in the texture atlas used by a face of the block on a frame of the game.
+    pub fn texture(&self, face: Face, frame_count: u32) -> u8 {
+        let texture = if face.is_top() {
+            self.top_texture
+        } else if face.is_bottom() {
+            self.bottom_texture
+        } else {
+            self.side_texture
+        };
+
+        texture + self.animation.frame(frame_count)
+    }
+}
+
+pub const BLOCKS: [Block; BLOCK_COUNT] = [
+    // AIR
+    Block {
+        solid: false,
+        opacity: Opacity::Translucent(0),
+        top_texture: 0,
+        bottom_texture: 0,
+        side_texture: 0,
+        shaded: false,
+        hardness: 0,
+        reflective: false,
+        emission: 0,
+        animation: Animation::NONE,
+    },
+    // STONE
+    Block::new(texture::STONE, 3),
+    // DIRT
+    Block::new(texture::DIRT, 1),
+    // GRASS
+    Block::with_faces(texture::GRASS_TOP, texture::DIRT, texture::GRASS_SIDE, 1),
+    // WOOD
+    Block::with_faces(texture::WOOD_TOP, texture::WOOD_TOP, texture::WOOD_SIDE, 2),
+    // PLANKS
+    Block::new(texture::PLANKS, 2),
+    // BEDROCK
+    Block::new(texture::BEDROCK, 0),
+    // GLASS
+    Block::new(texture::GLASS, 1).with_opacity(Opacity::Translucent(5)),
+    // LEAVES
+    Block::new(texture::LEAVES, 1).with_opacity(Opacity::Cutout),
+    // WATER, rays used to break and place blocks go through it to the blocks underneath.
+    Block::new(texture::WATER, 1)
+        .with_opacity(Opacity::Translucent(10))
+        .with_solid(false)
+        .with_animation(2, 30),
+    // MIRROR
+    Block::new(texture::MIRROR, 2).with_reflective(true),
+    // TORCH
+    Block::new(texture::TORCH, 1)
+        .with_opacity(Opacity::Cutout)
+        .with_emission(12)
+        .with_animation(2, 8),
+    // GLOWSTONE
+    Block::new(texture::GLOWSTONE, 2).with_emission(light::MAX_LIGHT),
+    // LAVA
+    Block::new(texture::LAVA, 1)
+        .with_emission(light::MAX_LIGHT)
+        .with_animation(2, 40),
+];
+
+pub fn get(voxel: u8) -> &'static Block {
+    &BLOCKS[voxel as usize]
+}
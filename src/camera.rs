@@ -1,3 +1,4 @@
+use crate::projection::Projection;
 use crate::vec3::*;
 use crate::wasm4::*;
 
@@ -6,17 +7,17 @@ const CAMERA_MAX_X_ROTATION: f32 = std::f32::consts::FRAC_PI_2 - 0.1;
 const CAMERA_MOVE_SPEED: f32 = 0.05;
 const CAMERA_DEFAULT_FORWARD: Vec3<f32> = Vec3::new(0.0, 0.0, 1.0);
 const CAMERA_DEFAULT_RIGHT: Vec3<f32> = Vec3::new(1.0, 0.0, 0.0);
+// Points closer than this in front of the camera aren't projected onto the screen.
+const CAMERA_NEAR: f32 = 0.1;
 
 pub struct Camera {
     pub position: Vec3<f32>,
     pub rotation: Vec3<f32>,
     pub forward: Vec3<f32>,
     pub right: Vec3<f32>,
-
-    pub rotation_x_sin: f32,
-    pub rotation_x_cos: f32,
-    pub rotation_y_sin: f32,
-    pub rotation_y_cos: f32,
+    pub projection: Projection,
+    // Precalculated from rotation.
+    pub orientation: Mat3,
 }
 
 impl Camera {
@@ -26,13 +27,17 @@ impl Camera {
             rotation: Vec3::new(0.0, 0.0, 0.0),
             forward: CAMERA_DEFAULT_FORWARD,
             right: CAMERA_DEFAULT_RIGHT,
-            rotation_x_sin: 0.0,
-            rotation_x_cos: 1.0,
-            rotation_y_sin: 0.0,
-            rotation_y_cos: 1.0,
+            projection: Projection::new(),
+            orientation: Mat3::IDENTITY,
         }
     }
 
+    pub fn set_pose(&mut self, position: Vec3<f32>, rotation: Vec3<f32>) {
+        self.position = position;
+        self.rotation = rotation;
+        self.update_rotation();
+    }
+
     pub fn update(&mut self, gamepad1: u8, gamepad2: u8) {
         self.rotate(gamepad1);
         self.step(gamepad2);
@@ -62,19 +67,41 @@ impl Camera {
             return;
         }
 
-        self.rotation.x = (self.rotation.x + rotate_x * CAMERA_ROTATION_SPEED)
+        self.rotation.x += rotate_x * CAMERA_ROTATION_SPEED;
+        self.rotation.y += rotate_y * CAMERA_ROTATION_SPEED;
+        self.update_rotation();
+    }
+
+    fn update_rotation(&mut self) {
+        self.rotation.x = self
+            .rotation
+            .x
             .clamp(-CAMERA_MAX_X_ROTATION, CAMERA_MAX_X_ROTATION);
-        self.rotation_x_sin = self.rotation.x.sin();
-        self.rotation_x_cos = self.rotation.x.cos();
 
-        // Precalculate sin/cos of the camera's rotation vector,
+        // Precalculate the camera's orientation from its rotation vector,
         // to reduce the number of computations done per-frame.
-        self.rotation.y += rotate_y * CAMERA_ROTATION_SPEED;
-        self.rotation_y_sin = self.rotation.y.sin();
-        self.rotation_y_cos = self.rotation.y.cos();
+        self.orientation = Mat3::from_rotation(&self.rotation);
 
-        self.forward = CAMERA_DEFAULT_FORWARD.rotated(&self.rotation);
-        self.right = CAMERA_DEFAULT_RIGHT.rotated(&self.rotation);
+        self.forward = self.orientation.transform(&CAMERA_DEFAULT_FORWARD);
+        self.right = self.orientation.transform(&CAMERA_DEFAULT_RIGHT);
+    }
+
+    // Project a point in the world onto the screen, the same way pixels are turned into rays.
+    // Returns the screen position in pixels and the point's depth in front of the camera,
+    // or None if the point is behind the camera.
+    pub fn project(&self, point: &Vec3<f32>) -> Option<Vec3<f32>> {
+        let relative = self.orientation.inverse_transform(&Vec3::new(
+            point.x - self.position.x,
+            point.y - self.position.y,
+            point.z - self.position.z,
+        ));
+
+        if relative.z < CAMERA_NEAR {
+            return None;
+        }
+
+        let (x, y) = self.projection.project(&relative);
+        Some(Vec3::new(x, y, relative.z))
     }
 
     fn step(&mut self, gamepad: u8) {
@@ -111,3 +138,9 @@ impl Camera {
         self.position.z += self.forward.z * move_z + self.right.z * move_x;
     }
 }
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
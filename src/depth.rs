@@ -0,0 +1,70 @@
+use crate::wasm4::SCREEN_SIZE;
+
+// Depths are packed into 4 bits per pixel, two pixels per byte.
+const DEPTH_BUFFER_SIZE: usize = SCREEN_SIZE as usize * SCREEN_SIZE as usize / 2;
+// Depth stored for pixels where nothing was hit, or that are at least as far as the range.
+pub const DEPTH_FAR: u8 = 0xf;
+
+// Distance from the camera to whatever was drawn at each pixel,
+// quantized to 4 bits per pixel over the range that gets raycast.
+pub struct DepthBuffer {
+    depths: [u8; DEPTH_BUFFER_SIZE],
+    // Distance represented by DEPTH_FAR.
+    range: f32,
+}
+
+impl DepthBuffer {
+    pub const fn new() -> Self {
+        Self {
+            depths: [DEPTH_FAR << 4 | DEPTH_FAR; DEPTH_BUFFER_SIZE],
+            range: 1.0,
+        }
+    }
+
+    // Reset every pixel to DEPTH_FAR, ready to draw a new frame covering the given range.
+    pub fn clear(&mut self, range: f32) {
+        self.depths.fill(DEPTH_FAR << 4 | DEPTH_FAR);
+        self.range = range;
+    }
+
+    pub fn quantize(&self, distance: f32) -> u8 {
+        // Rounds up, so surfaces are never stored closer than they really are.
+        (distance / self.range * DEPTH_FAR as f32)
+            .ceil()
+            .clamp(0.0, DEPTH_FAR as f32) as u8
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, distance: f32) {
+        let index = y * SCREEN_SIZE as usize + x;
+        let shift = (index & 1) << 2;
+        let depth = self.quantize(distance);
+        let packed_depths = &mut self.depths[index >> 1];
+        *packed_depths = (depth << shift) | (*packed_depths & !(0xf << shift));
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        let index = y * SCREEN_SIZE as usize + x;
+        let shift = (index & 1) << 2;
+        (self.depths[index >> 1] >> shift) & 0xf
+    }
+
+    // Get the approximate distance to what was drawn at a pixel, None if nothing was hit within range.
+    pub fn distance(&self, x: usize, y: usize) -> Option<f32> {
+        match self.get(x, y) {
+            DEPTH_FAR => None,
+            depth => Some(depth as f32 / DEPTH_FAR as f32 * self.range),
+        }
+    }
+
+    // Depth test, check if something at a distance would be in front of the scene at a pixel.
+    // Ties pass, with only 16 depths a sprite standing just in front of a wall often shares its depth.
+    pub fn is_visible(&self, x: usize, y: usize, distance: f32) -> bool {
+        self.quantize(distance) <= self.get(x, y)
+    }
+}
+
+impl Default for DepthBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
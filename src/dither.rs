@@ -0,0 +1,25 @@
+#[rustfmt::skip]
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [ 0,  8,  2, 10],
+    [12,  4, 14,  6],
+    [ 3, 11,  1,  9],
+    [15,  7, 13,  5],
+];
+
+// Number of distinct levels the 4x4 matrix can represent.
+pub const LEVELS: u8 = 16;
+// Number of shading bands between each palette color,
+// shading is rounded to these before being dithered.
+pub const SHADE_BANDS: f32 = 4.0;
+
+// Ordered dithering, returns true if a pixel should be filled
+// when (level / LEVELS) of the pixels in an area should be filled.
+pub fn dither(level: u8, x: usize, y: usize) -> bool {
+    BAYER_4X4[y & 3][x & 3] < level
+}
+
+// Same as dither, but with the fill amount given as a 0.0-1.0 fraction.
+pub fn dither_fraction(fraction: f32, x: usize, y: usize) -> bool {
+    let level = (fraction * LEVELS as f32).clamp(0.0, LEVELS as f32) as u8;
+    dither(level, x, y)
+}
@@ -0,0 +1,65 @@
+use crate::vec3::*;
+
+// One of the six faces of a voxel, named after the direction its normal points in.
+// The world's y axis points down, so NegY is the top of a block.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Face {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl Face {
+    // Get the face that a ray steps through when it enters a voxel,
+    // moving along an axis (0 = x, 1 = y, 2 = z) in the direction of step.
+    pub fn entered_through(axis: usize, step: i32) -> Self {
+        match (axis, step > 0) {
+            (0, true) => Face::NegX,
+            (0, false) => Face::PosX,
+            (1, true) => Face::NegY,
+            (1, false) => Face::PosY,
+            (2, true) => Face::NegZ,
+            _ => Face::PosZ,
+        }
+    }
+
+    // 0 = x, 1 = y, 2 = z
+    pub fn axis(self) -> usize {
+        match self {
+            Face::PosX | Face::NegX => 0,
+            Face::PosY | Face::NegY => 1,
+            Face::PosZ | Face::NegZ => 2,
+        }
+    }
+
+    pub fn normal(self) -> Vec3<i32> {
+        match self {
+            Face::PosX => Vec3::new(1, 0, 0),
+            Face::NegX => Vec3::new(-1, 0, 0),
+            Face::PosY => Vec3::new(0, 1, 0),
+            Face::NegY => Vec3::new(0, -1, 0),
+            Face::PosZ => Vec3::new(0, 0, 1),
+            Face::NegZ => Vec3::new(0, 0, -1),
+        }
+    }
+
+    // Axes that a RayHit's u and v coordinates increase along on this face.
+    pub fn tangents(self) -> (Vec3<i32>, Vec3<i32>) {
+        match self.axis() {
+            0 => (Vec3::new(0, 0, 1), Vec3::new(0, 1, 0)),
+            1 => (Vec3::new(1, 0, 0), Vec3::new(0, 0, 1)),
+            _ => (Vec3::new(1, 0, 0), Vec3::new(0, 1, 0)),
+        }
+    }
+
+    pub fn is_top(self) -> bool {
+        self == Face::NegY
+    }
+
+    pub fn is_bottom(self) -> bool {
+        self == Face::PosY
+    }
+}
@@ -0,0 +1,34 @@
+use crate::dither;
+
+pub struct Fog {
+    // Distance where the fog starts to cover the scene.
+    pub start: f32,
+    // Distance where the fog completely covers the scene,
+    // nothing past this distance needs to be raycast.
+    pub end: f32,
+    // Palette color of the fog, None fades the scene into the sky instead.
+    pub color: Option<u16>,
+}
+
+impl Fog {
+    pub const fn new() -> Self {
+        Self {
+            start: 12.0,
+            end: 24.0,
+            color: None,
+        }
+    }
+
+    // Check if a pixel should be covered by fog, dithering between the scene and the fog
+    // so that the fog covers more pixels in each band of distance.
+    pub fn covers(&self, distance: f32, x: usize, y: usize) -> bool {
+        let fraction = (distance - self.start) / (self.end - self.start);
+        dither::dither_fraction(fraction, x, y)
+    }
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
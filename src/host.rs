@@ -0,0 +1,245 @@
+use crate::palette;
+use crate::platform::*;
+use crate::screenshot::*;
+use std::path::PathBuf;
+
+use crate::wasm4::SCREEN_SIZE;
+
+// WASM-4 gives each cart 1024 bytes of persistent storage.
+const DISK_SIZE: usize = 1024;
+// Gray levels used for palette colors 0-3 when saving the framebuffer as a PGM image.
+const PGM_LEVELS: [u8; 4] = [255, 170, 85, 0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tone {
+    pub frequency: u32,
+    pub duration: u32,
+    pub volume: u32,
+    pub flags: u32,
+}
+
+// In-memory platform used when running the game natively,
+// everything that would normally reach the console is recorded instead.
+pub struct HostPlatform {
+    pub framebuffer: [u8; FRAMEBUFFER_SIZE],
+    pub palette: [u32; 4],
+    pub draw_colors: u16,
+    pub gamepads: [u8; 4],
+    pub disk: Vec<u8>,
+    pub tones: Vec<Tone>,
+    pub traces: Vec<String>,
+    pub screenshots: Vec<[u8; FRAMEBUFFER_SIZE]>,
+    // Screenshots are also saved here as PGM images when it's set.
+    pub screenshot_directory: Option<PathBuf>,
+    // Why the last screenshot couldn't be saved, None if it was.
+    pub last_screenshot_error: Option<std::io::Error>,
+}
+
+impl HostPlatform {
+    pub fn new() -> Self {
+        Self {
+            framebuffer: [0; FRAMEBUFFER_SIZE],
+            // Same defaults as WASM-4.
+            palette: palette::Theme::Default.colors(),
+            draw_colors: 0x1203,
+            gamepads: [0; 4],
+            disk: Vec::new(),
+            tones: Vec::new(),
+            traces: Vec::new(),
+            screenshots: Vec::new(),
+            screenshot_directory: None,
+            last_screenshot_error: None,
+        }
+    }
+
+    // Get the palette index (0-3) of a pixel in the framebuffer.
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        get_framebuffer_pixel(&self.framebuffer, x, y)
+    }
+}
+
+impl Default for HostPlatform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Platform for HostPlatform {
+    fn framebuffer(&mut self) -> &mut [u8; FRAMEBUFFER_SIZE] {
+        &mut self.framebuffer
+    }
+
+    fn palette(&self) -> [u32; 4] {
+        self.palette
+    }
+
+    fn set_palette(&mut self, palette: [u32; 4]) {
+        self.palette = palette;
+    }
+
+    fn draw_colors(&self) -> u16 {
+        self.draw_colors
+    }
+
+    fn set_draw_colors(&mut self, draw_colors: u16) {
+        self.draw_colors = draw_colors;
+    }
+
+    fn gamepad(&self, player: usize) -> u8 {
+        self.gamepads[player.min(self.gamepads.len() - 1)]
+    }
+
+    // Matches WASM-4's rect, the first draw color fills and the second draw color outlines,
+    // a transparent outline leaves the fill covering the whole rectangle.
+    fn rect(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        let fill_color = (self.draw_colors & 0xf) as u8;
+        let stroke_color = ((self.draw_colors >> 4) & 0xf) as u8;
+        let screen_size = SCREEN_SIZE as i32;
+
+        for rect_y in 0..height as i32 {
+            for rect_x in 0..width as i32 {
+                let pixel_x = x + rect_x;
+                let pixel_y = y + rect_y;
+
+                if pixel_x < 0 || pixel_y < 0 || pixel_x >= screen_size || pixel_y >= screen_size {
+                    continue;
+                }
+
+                let is_edge = rect_x == 0
+                    || rect_y == 0
+                    || rect_x == width as i32 - 1
+                    || rect_y == height as i32 - 1;
+                let color = if is_edge && stroke_color != 0 {
+                    stroke_color
+                } else {
+                    fill_color
+                };
+                if color == 0 {
+                    // Transparent
+                    continue;
+                }
+
+                set_framebuffer_pixel(
+                    &mut self.framebuffer,
+                    pixel_x as usize,
+                    pixel_y as usize,
+                    color - 1,
+                );
+            }
+        }
+    }
+
+    fn disk_read(&mut self, dest: &mut [u8]) -> u32 {
+        let size = dest.len().min(self.disk.len());
+        dest[..size].copy_from_slice(&self.disk[..size]);
+        size as u32
+    }
+
+    fn disk_write(&mut self, src: &[u8]) -> u32 {
+        let size = src.len().min(DISK_SIZE);
+        self.disk.clear();
+        self.disk.extend_from_slice(&src[..size]);
+        size as u32
+    }
+
+    fn tone(&mut self, frequency: u32, duration: u32, volume: u32, flags: u32) {
+        self.tones.push(Tone {
+            frequency,
+            duration,
+            volume,
+            flags,
+        });
+    }
+
+    fn trace(&mut self, text: &str) {
+        self.traces.push(text.to_string());
+    }
+
+    fn screenshot(&mut self) {
+        self.screenshots.push(self.framebuffer);
+
+        if let Some(directory) = &self.screenshot_directory {
+            let path = directory.join(format!("screenshot_{}.pgm", self.screenshots.len()));
+            self.last_screenshot_error = std::fs::write(path, encode_pgm(&self.framebuffer)).err();
+        }
+    }
+}
+
+// Encode a framebuffer as a binary (P5) PGM image.
+pub fn encode_pgm(framebuffer: &[u8; FRAMEBUFFER_SIZE]) -> Vec<u8> {
+    let screen_size = SCREEN_SIZE as usize;
+    let mut pgm = format!("P5\n{} {}\n255\n", screen_size, screen_size).into_bytes();
+
+    for y in 0..screen_size {
+        for x in 0..screen_size {
+            pgm.push(PGM_LEVELS[get_framebuffer_pixel(framebuffer, x, y) as usize]);
+        }
+    }
+
+    pgm
+}
+
+// Decode a PGM image written by encode_pgm back into a framebuffer,
+// returns None if the image isn't a screen sized P5 PGM using the expected gray levels.
+pub fn decode_pgm(pgm: &[u8]) -> Option<[u8; FRAMEBUFFER_SIZE]> {
+    let screen_size = SCREEN_SIZE as usize;
+    let header = format!("P5\n{} {}\n255\n", screen_size, screen_size).into_bytes();
+    let pixels = pgm.strip_prefix(header.as_slice())?;
+
+    if pixels.len() != screen_size * screen_size {
+        return None;
+    }
+
+    let mut framebuffer = [0; FRAMEBUFFER_SIZE];
+    for (i, level) in pixels.iter().enumerate() {
+        let color = PGM_LEVELS.iter().position(|l| l == level)?;
+        set_framebuffer_pixel(
+            &mut framebuffer,
+            i % screen_size,
+            i / screen_size,
+            color as u8,
+        );
+    }
+
+    Some(framebuffer)
+}
+
+// Decode the last screenshot a cart wrote to the debug console, see screenshot::trace_screenshot.
+// Returns None if the traces don't contain a complete screenshot.
+pub fn decode_traced_screenshot(traces: &[String]) -> Option<[u8; FRAMEBUFFER_SIZE]> {
+    let begin = traces.iter().rposition(|line| line == SCREENSHOT_BEGIN)?;
+    let length = traces[begin + 1..]
+        .iter()
+        .position(|line| line == SCREENSHOT_END)?;
+
+    let mut bytes = Vec::new();
+    for line in &traces[begin + 1..begin + 1 + length] {
+        decode_base64(line, &mut bytes)?;
+    }
+
+    bytes.try_into().ok()
+}
+
+fn decode_base64(text: &str, bytes: &mut Vec<u8>) -> Option<()> {
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+
+    for group in text.as_bytes().chunks(4) {
+        let mut packed = 0;
+        let mut padding = 0;
+        for &character in group {
+            let value = if character == b'=' {
+                padding += 1;
+                0
+            } else {
+                BASE64_ALPHABET.iter().position(|&c| c == character)? as u32
+            };
+            packed = packed << 6 | value;
+        }
+
+        bytes.extend_from_slice(&packed.to_be_bytes()[1..4 - padding]);
+    }
+
+    Some(())
+}
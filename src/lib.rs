@@ -1,61 +1,47 @@
+#[cfg(all(feature = "buddy-alloc", target_arch = "wasm32"))]
 mod alloc;
-mod camera;
+mod ambient_occlusion;
+pub mod block;
+pub mod camera;
+pub mod depth;
+mod dither;
+pub mod face;
+pub mod fog;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod host;
+pub mod light;
+pub mod map;
+pub mod minimap;
+pub mod outline;
+pub mod overview;
+pub mod palette;
+pub mod platform;
+pub mod projection;
+#[cfg(test)]
+mod render_tests;
 mod rng;
-mod vec3;
-#[cfg(feature = "buddy-alloc")]
+pub mod screenshot;
+pub mod settings;
+pub mod sky;
+pub mod sprite;
+pub mod sun;
+mod texture;
+pub mod vec3;
 mod wasm4;
 
+use crate::face::Face;
+use crate::map::{BRICK_SIZE, MAP_SIZE};
+use crate::platform::*;
+use crate::texture::TEXTURE_SIZE;
 use crate::vec3::*;
+#[cfg(test)]
+use std::cell::Cell;
 use wasm4::*;
 
-#[rustfmt::skip]
-const SMILEY: [u8; 8] = [
-    0b11000011,
-    0b10000001,
-    0b00100100,
-    0b00100100,
-    0b00000000,
-    0b00100100,
-    0b10011001,
-    0b11000011,
-];
-
-const MAP_SIZE: usize = 32;
-const MAP_LENGTH: usize = MAP_SIZE * MAP_SIZE * MAP_SIZE;
-const RAY_RANGE: f32 = 24.0;
-const SHADOW_DISTANCE: f32 = 16.0;
 const SCREEN_WIDTH: usize = 160;
 const SCREEN_HEIGHT: usize = 160;
-const ASPECT_RATIO: f32 = 1.0;
-const HEIGHT: f32 = 2.0;
-const WIDTH: f32 = ASPECT_RATIO * HEIGHT;
-const FOCAL_LENGTH: f32 = 1.0;
-const LOWER_LEFT_CORNER: Vec3::<f32> = Vec3::new(-WIDTH * 0.5, -HEIGHT * 0.5, FOCAL_LENGTH);
-const TEXTURE_SIZE: usize = 8;
 const INTERACT_DISTANCE: f32 = 6.0;
-
-// From the WASM-4 documentation:
-fn pixel(x: usize, y: usize) {
-    // The byte index into the framebuffer that contains (x, y)
-    let idx = (y * 160 + x) >> 2;
-
-    // Calculate the bits within the byte that corresponds to our position
-    let shift = (x as u8 & 0b11) << 1;
-    let mask = 0b11 << shift;
-
-    unsafe {
-        let palette_color: u8 = (*DRAW_COLORS & 0xf) as u8;
-        if palette_color == 0 {
-            // Transparent
-            return;
-        }
-        let color = (palette_color - 1) & 0b11;
-
-        let framebuffer = &mut *FRAMEBUFFER;
-
-        framebuffer[idx] = (color << shift) | (framebuffer[idx] & !mask);
-    }
-}
+const SHADOW_RAY_OFFSET: f32 = 1e-3;
 
 // Quake's fast inverse square root:
 fn inv_sqrt(x: f32) -> f32 {
@@ -66,89 +52,205 @@ fn inv_sqrt(x: f32) -> f32 {
     y * (1.5 - 0.5 * x * y * y)
 }
 
-struct RayHit {
-    distance: f32,
-    // TODO: Convert to enum?
-    // 0 = x, 1 = y, 2 = z
-    hit_side: u16,
-    block: Option<Vec3<i32>>,
+pub struct RayHit {
+    pub distance: f32,
+    pub face: Face,
+    // Exact position where the ray hit the face.
+    pub point: Vec3<f32>,
+    // Texture coordinates of the hit point, ranging from 0.0 to 1.0 across the face.
+    pub u: f32,
+    pub v: f32,
+    pub block: Vec3<i32>,
+    pub voxel: u8,
 }
 
-struct Game {
+pub struct Game {
     frame_count: u32,
-    map: [u8; MAP_LENGTH],
+    map: map::Map,
     rng: rng::Rng,
-    camera: camera::Camera,
+    pub camera: camera::Camera,
+    pub fog: fog::Fog,
+    pub sun: sun::Sun,
+    pub sky: sky::Sky,
+    pub outline: outline::Outline,
+    pub minimap: minimap::Minimap,
+    pub overview: overview::Overview,
+    depth_buffer: depth::DepthBuffer,
+    pub sprites: sprite::Sprites,
+    pub palette: palette::Palette,
+    // Settings as they were last loaded or saved.
+    saved_settings: settings::Settings,
+    // Maximum number of times a ray can bounce off of mirrors.
+    pub max_reflections: u8,
+    // Number of DDA steps taken by every raycast this frame, only counted in tests.
+    #[cfg(test)]
+    ray_steps: Cell<u32>,
     previous_gamepad1: u8,
+    previous_gamepad2: u8,
+    selected_block: u8,
+    break_target: Vec3<i32>,
+    break_hits: u8,
 }
 
 impl Game {
-    const fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             frame_count: 0,
-            map: [0; MAP_LENGTH],
+            map: map::Map::new(),
             rng: rng::Rng::new(777),
             camera: camera::Camera::new(),
+            fog: fog::Fog::new(),
+            sun: sun::Sun::new(),
+            sky: sky::Sky::new(),
+            outline: outline::Outline::new(),
+            minimap: minimap::Minimap::new(),
+            overview: overview::Overview::new(),
+            depth_buffer: depth::DepthBuffer::new(),
+            sprites: sprite::Sprites::new(),
+            palette: palette::Palette::new(),
+            saved_settings: settings::Settings::new(),
+            max_reflections: 3,
+            #[cfg(test)]
+            ray_steps: Cell::new(0),
             previous_gamepad1: 0,
+            previous_gamepad2: 0,
+            selected_block: block::STONE,
+            break_target: Vec3::new(0, 0, 0),
+            break_hits: 0,
         }
     }
 
-    fn start(&mut self) {
+    pub fn start(&mut self) {
         self.generate_map();
     }
 
-    fn update(&mut self, gamepad1: u8, gamepad2: u8) {
-        self.camera.update(gamepad1, gamepad2);
+    pub fn update<P: Platform>(&mut self, platform: &mut P) {
+        #[cfg(test)]
+        self.ray_steps.set(0);
+        let gamepad1 = platform.gamepad(0);
+        let gamepad2 = platform.gamepad(1);
+        // Holding button 2 on the second gamepad turns its arrows and button 1 into shortcuts,
+        // instead of moving and picking blocks.
+        let shortcuts = gamepad2 & BUTTON_2 != 0;
+        let arrows = BUTTON_LEFT | BUTTON_RIGHT | BUTTON_UP | BUTTON_DOWN;
+        self.camera.update(
+            gamepad1,
+            if shortcuts {
+                gamepad2 & !arrows
+            } else {
+                gamepad2
+            },
+        );
+        // The sun and moon follow the time of day, unless the sun was given a direction.
+        if self.sun.follows_sky {
+            self.sun.direction = self.sky.light_direction();
+        }
 
         let pressed_this_frame = gamepad1 & (gamepad1 ^ self.previous_gamepad1);
+        let pressed_this_frame2 = gamepad2 & (gamepad2 ^ self.previous_gamepad2);
+
+        if pressed_this_frame2 & BUTTON_1 != 0 && !shortcuts {
+            // Cycle through every block except air and unbreakable blocks.
+            loop {
+                self.selected_block = self.selected_block % (block::BLOCK_COUNT as u8 - 1) + 1;
+
+                if block::get(self.selected_block).hardness != 0 {
+                    break;
+                }
+            }
+        }
+
+        if shortcuts {
+            if pressed_this_frame2 & BUTTON_LEFT != 0 {
+                self.palette.set_theme(self.palette.theme().previous());
+            }
+
+            if pressed_this_frame2 & BUTTON_RIGHT != 0 {
+                self.palette.set_theme(self.palette.theme().next());
+            }
+
+            if pressed_this_frame2 & BUTTON_UP != 0 {
+                self.minimap.enabled = !self.minimap.enabled;
+            }
+
+            if pressed_this_frame2 & BUTTON_DOWN != 0 {
+                self.overview.enabled = !self.overview.enabled;
+            }
+        }
 
         if pressed_this_frame & BUTTON_1 != 0 {
-            let ray_hit = self.raycast(
-                self.camera.position,
-                &self.camera.forward,
-                INTERACT_DISTANCE,
-                true,
-            );
+            let ray_hit = self.raycast_solid(self.camera.position, &self.camera.forward);
             if let Some(ray_hit) = ray_hit {
-                let hit_block = ray_hit.block.unwrap();
-                self.set_map(&hit_block, 0);
+                let hit_block = ray_hit.block;
+                let hardness = block::get(ray_hit.voxel).hardness;
+
+                // Harder blocks need to be hit multiple times in a row.
+                if hardness != 0 {
+                    if self.break_hits != 0 && hit_block == self.break_target {
+                        self.break_hits += 1;
+                    } else {
+                        self.break_target = hit_block;
+                        self.break_hits = 1;
+                    }
+
+                    if self.break_hits >= hardness {
+                        self.set_map(&hit_block, block::AIR);
+                        self.break_hits = 0;
+                    }
+                }
             }
         } else if pressed_this_frame & BUTTON_2 != 0 {
-            let ray_hit = self.raycast(
-                self.camera.position,
-                &self.camera.forward,
-                INTERACT_DISTANCE,
-                true,
-            );
+            let ray_hit = self.raycast_solid(self.camera.position, &self.camera.forward);
             if let Some(ray_hit) = ray_hit {
-                let mut target_block = ray_hit.block.unwrap();
-
-                match ray_hit.hit_side {
-                    0 => target_block.x -= self.camera.forward.x.signum() as i32,
-                    1 => target_block.y -= self.camera.forward.y.signum() as i32,
-                    2 => target_block.z -= self.camera.forward.z.signum() as i32,
-                    _ => {}
-                }
+                // Place the new block against the face that was hit.
+                let normal = ray_hit.face.normal();
+                let target_block = Vec3::new(
+                    ray_hit.block.x + normal.x,
+                    ray_hit.block.y + normal.y,
+                    ray_hit.block.z + normal.z,
+                );
 
-                self.set_map(&target_block, 1);
+                self.set_map(&target_block, self.selected_block);
             }
         }
 
-        for y in 0..SCREEN_HEIGHT {
-            let v = y as f32 / SCREEN_HEIGHT as f32;
-            for x in 0..SCREEN_WIDTH {
-                let u = x as f32 / SCREEN_WIDTH as f32;
-                let mut direction = Vec3::<f32> {
-                    x: LOWER_LEFT_CORNER.x + u * WIDTH,
-                    y: LOWER_LEFT_CORNER.y + v * HEIGHT,
-                    z: LOWER_LEFT_CORNER.z,
-                };
-                direction.rotate_by_precalculated(
-                    self.camera.rotation_x_sin,
-                    self.camera.rotation_x_cos,
-                    self.camera.rotation_y_sin,
-                    self.camera.rotation_y_cos,
-                );
+        // Find the block the player is looking at after any changes to the map.
+        let target = self.raycast_solid(self.camera.position, &self.camera.forward);
+        self.outline.set_target(target.as_ref());
+        self.depth_buffer.clear(self.fog.end);
+
+        if self.overview.enabled {
+            self.draw_overview(platform);
+        } else {
+            self.draw_perspective(platform);
+        }
+
+        self.draw_minimap(platform);
+
+        self.palette.update();
+        platform.set_palette(self.palette.colors());
+        self.save_settings(platform);
+
+        // Screenshots are taken last so they show the whole frame.
+        if shortcuts && pressed_this_frame2 & BUTTON_1 != 0 {
+            platform.screenshot();
+        }
+
+        self.sky.update();
+        self.frame_count += 1;
+        self.previous_gamepad1 = gamepad1;
+        self.previous_gamepad2 = gamepad2;
+    }
+
+    // Draw the view from the camera, with sprites and a crosshair on top.
+    fn draw_perspective<P: Platform>(&mut self, platform: &mut P) {
+        let viewport = self.camera.projection.viewport();
+        for y in viewport.y..viewport.y + viewport.height {
+            for x in viewport.x..viewport.x + viewport.width {
+                let mut direction = self
+                    .camera
+                    .orientation
+                    .transform(&self.camera.projection.ray_direction(x, y));
                 // Normalize the direction vector:
                 let direction_inv_sqrt = inv_sqrt(
                     direction.x * direction.x
@@ -159,119 +261,336 @@ impl Game {
                 direction.y *= direction_inv_sqrt;
                 direction.z *= direction_inv_sqrt;
 
-                // Modify range to add dithering effect.
-                let range = if (x + y) & 1 == 0 {
-                    SHADOW_DISTANCE
-                } else {
-                    RAY_RANGE
-                };
-                let ray_hit = self.raycast(self.camera.position, &direction, range, false);
-                let color = Self::hit_to_color(ray_hit, &self.camera.position, &direction);
-                unsafe { *DRAW_COLORS = color }
-                pixel(x, y);
+                let (color, distance) =
+                    self.trace(self.camera.position, direction, self.fog.end, true, x, y);
+                self.depth_buffer.set(x, y, distance);
+                platform.set_draw_colors(color);
+                platform.pixel(x, y);
             }
         }
 
-        // Draw the crosshair:
-        unsafe { *DRAW_COLORS = 0x41 }
-        rect(78, 78, 4, 4);
+        self.draw_sprites(platform);
 
-        self.frame_count += 1;
-        self.previous_gamepad1 = gamepad1;
+        // Draw the crosshair in the middle of the view:
+        platform.set_draw_colors(0x41);
+        platform.rect(
+            (viewport.x + viewport.width / 2) as i32 - 2,
+            (viewport.y + viewport.height / 2) as i32 - 2,
+            4,
+            4,
+        );
+    }
+
+    // Number of DDA steps taken by every raycast during the last frame.
+    #[cfg(test)]
+    pub fn ray_steps(&self) -> u32 {
+        self.ray_steps.get()
+    }
+
+    pub fn depth_buffer(&self) -> &depth::DepthBuffer {
+        &self.depth_buffer
+    }
+
+    // Load the player's settings from persistent storage, if any were saved.
+    pub fn load_settings<P: Platform>(&mut self, platform: &mut P) {
+        let mut bytes = [0; settings::SETTINGS_SIZE];
+        let size = platform.disk_read(&mut bytes) as usize;
+
+        if let Some(settings) = settings::Settings::from_bytes(&bytes[..size]) {
+            self.palette.set_theme_instantly(settings.theme);
+            self.saved_settings = settings;
+        }
+    }
+
+    pub fn settings(&self) -> settings::Settings {
+        settings::Settings {
+            theme: self.palette.theme(),
+        }
+    }
+
+    // Write the settings to persistent storage whenever they change.
+    fn save_settings<P: Platform>(&mut self, platform: &mut P) {
+        let settings = self.settings();
+        if settings != self.saved_settings {
+            platform.disk_write(&settings.to_bytes());
+            self.saved_settings = settings;
+        }
+    }
+
+    pub fn set_boundary(&mut self, boundary: map::Boundary) {
+        self.map.set_boundary(boundary);
     }
 
     fn generate_map(&mut self) {
         let min_y = MAP_SIZE / 2;
 
         for z in 0..MAP_SIZE {
-            for y in min_y..MAP_SIZE {
-                for x in 0..MAP_SIZE {
-                    self.map[x + y * MAP_SIZE + z * MAP_SIZE * MAP_SIZE] = 1;
+            for x in 0..MAP_SIZE {
+                let dirt_depth = 2 + self.rng.range(3) as usize;
+
+                for y in min_y..MAP_SIZE {
+                    let voxel = if y == min_y {
+                        block::GRASS
+                    } else if y <= min_y + dirt_depth {
+                        block::DIRT
+                    } else {
+                        block::STONE
+                    };
+
+                    self.set_map(&Vec3::new(x as i32, y as i32, z as i32), voxel);
                 }
             }
         }
+
+        // Add some tree trunks sticking out of the grass.
+        for _ in 0..8 {
+            let x = self.rng.range(MAP_SIZE as u32) as i32;
+            let z = self.rng.range(MAP_SIZE as u32) as i32;
+            let height = 3 + self.rng.range(3) as i32;
+
+            for y in 0..height {
+                self.set_map(&Vec3::new(x, min_y as i32 - 1 - y, z), block::WOOD);
+            }
+        }
     }
 
-    fn hit_map(&self, position: &Vec3<i32>) -> bool {
-        let wrapped_position = Vec3::<usize> {
-            x: position.x as usize & (MAP_SIZE - 1),
-            y: position.y as usize & (MAP_SIZE - 1),
-            z: position.z as usize & (MAP_SIZE - 1),
+    // Find the distance along a ray where it crosses out of the current brick on one axis.
+    fn brick_exit(position: i32, tile_dir: i32, dist_to_next: f32, ray_step: f32) -> f32 {
+        let position_in_brick = position & (BRICK_SIZE as i32 - 1);
+        let steps_in_brick = if tile_dir > 0 {
+            BRICK_SIZE as i32 - 1 - position_in_brick
+        } else {
+            position_in_brick
         };
 
-        unsafe {
-            *self.map.get_unchecked(
-                wrapped_position.x
-                    + wrapped_position.y * MAP_SIZE
-                    + wrapped_position.z * MAP_SIZE * MAP_SIZE,
-            ) == 1
+        let mut exit = dist_to_next;
+        for _ in 0..steps_in_brick {
+            exit += ray_step;
         }
+
+        exit
+    }
+
+    fn hit_map(&self, position: &Vec3<i32>) -> bool {
+        self.map.is_occupied(position)
+    }
+
+    fn get_map(&self, position: &Vec3<i32>) -> u8 {
+        self.map.get(position)
     }
 
     fn set_map(&mut self, position: &Vec3<i32>, voxel: u8) {
-        unsafe {
-            let wrapped_position = Vec3::<usize> {
-                x: position.x as usize & (MAP_SIZE - 1),
-                y: position.y as usize & (MAP_SIZE - 1),
-                z: position.z as usize & (MAP_SIZE - 1),
-            };
+        self.map.set(position, voxel);
+    }
 
-            *self.map.get_unchecked_mut(
-                wrapped_position.x
-                    + wrapped_position.y * MAP_SIZE
-                    + wrapped_position.z * MAP_SIZE * MAP_SIZE,
-            ) = voxel;
+    // Follow a ray through any reflections and get the color it ends up with,
+    // along with the distance to the first surface it hit for the depth buffer.
+    // Fog and the outline only apply to rays drawing the perspective view.
+    pub(crate) fn trace(
+        &self,
+        mut start: Vec3<f32>,
+        mut direction: Vec3<f32>,
+        max_range: f32,
+        perspective: bool,
+        x: usize,
+        y: usize,
+    ) -> (u16, f32) {
+        let mut range = max_range;
+        let mut traveled = 0.0;
+        let mut reflections = 0;
+        let mut depth = f32::INFINITY;
+
+        loop {
+            let mut ray_hit = self.raycast_visible(start, &direction, range, x, y);
+            if reflections == 0 {
+                depth = ray_hit
+                    .as_ref()
+                    .map_or(f32::INFINITY, |ray_hit| ray_hit.distance);
+            }
+
+            if let Some(ray_hit) = &mut ray_hit {
+                // Fog depends on how far the ray went in total, not just since the last bounce.
+                ray_hit.distance += traveled;
+
+                if reflections < self.max_reflections
+                    && self.is_reflective(ray_hit)
+                    && !(perspective && self.fog.covers(ray_hit.distance, x, y))
+                {
+                    // Flip the direction along the axis of the face that was hit,
+                    // then continue from just in front of the face.
+                    match ray_hit.face.axis() {
+                        0 => direction.x = -direction.x,
+                        1 => direction.y = -direction.y,
+                        _ => direction.z = -direction.z,
+                    }
+                    let normal = ray_hit.face.normal();
+                    start = Vec3::<f32> {
+                        x: ray_hit.point.x + normal.x as f32 * SHADOW_RAY_OFFSET,
+                        y: ray_hit.point.y + normal.y as f32 * SHADOW_RAY_OFFSET,
+                        z: ray_hit.point.z + normal.z as f32 * SHADOW_RAY_OFFSET,
+                    };
+                    range = max_range - ray_hit.distance;
+                    traveled = ray_hit.distance;
+                    reflections += 1;
+                    continue;
+                }
+            }
+
+            return (
+                self.hit_to_color(ray_hit, &direction, perspective, x, y),
+                depth,
+            );
         }
     }
 
-    fn hit_to_color(ray_hit: Option<RayHit>, start: &Vec3<f32>, direction: &Vec3<f32>) -> u16 {
+    fn hit_to_color(
+        &self,
+        ray_hit: Option<RayHit>,
+        direction: &Vec3<f32>,
+        perspective: bool,
+        x: usize,
+        y: usize,
+    ) -> u16 {
         // Check if the ray hit nothing:
         if ray_hit.is_none() {
-            return 4;
+            return self.sky.color(direction, x, y);
         }
 
         let ray_hit = ray_hit.unwrap();
 
-        // Take the absolute value to keep the wrapping math
-        // working even at negative coordinates.
-        let hit_position = Vec3::<f32> {
-            x: (start.x + ray_hit.distance * direction.x).abs(),
-            y: (start.y + ray_hit.distance * direction.y).abs(),
-            z: (start.z + ray_hit.distance * direction.z).abs(),
-        };
+        // Skip shading pixels that end up hidden by the fog.
+        if perspective && self.fog.covers(ray_hit.distance, x, y) {
+            return match self.fog.color {
+                Some(color) => color,
+                None => self.sky.color(direction, x, y),
+            };
+        }
 
-        // Find texture uv, multiply by texture size then
-        // wrap to stay within the texture's bounds.
-        // "a ^ (x-1)" is the same as "a % x".
-        // Vertical and horizontal texture mapping requires different math.
-        unsafe {
-            let u;
-            let v;
-
-            if ray_hit.hit_side == 1 {
-                u = (hit_position.x * TEXTURE_SIZE as f32).to_int_unchecked::<usize>()
-                    & (TEXTURE_SIZE - 1);
-                v = (hit_position.z * TEXTURE_SIZE as f32).to_int_unchecked::<usize>()
-                    & (TEXTURE_SIZE - 1);
-            } else {
-                u = ((hit_position.x + hit_position.z) * TEXTURE_SIZE as f32)
-                    .to_int_unchecked::<usize>()
-                    & (TEXTURE_SIZE - 1);
-                v = (hit_position.y * TEXTURE_SIZE as f32).to_int_unchecked::<usize>()
-                    & (TEXTURE_SIZE - 1);
+        let color = self.surface_color(&ray_hit, x, y);
+        if perspective {
+            self.outline
+                .apply(&ray_hit, &self.camera.projection, color, x, y)
+        } else {
+            color
+        }
+    }
+
+    // Get the color of a surface that a ray hit, textured and lit.
+    fn surface_color(&self, ray_hit: &RayHit, x: usize, y: usize) -> u16 {
+        let block = block::get(ray_hit.voxel);
+        let shading = if block.shaded {
+            let mut light = self.sun.lambert(ray_hit.face);
+            if light > 0.0 && self.sun.shadows && self.in_shadow(ray_hit) {
+                light = 0.0;
             }
 
-            ray_hit.hit_side + 1 + (((SMILEY[v] & (1 << u)) != 0) as u16)
+            // Block light comes from the air in front of the face, it lights up caves and shadows.
+            let normal = ray_hit.face.normal();
+            let block_light = self.map.light(&Vec3::new(
+                ray_hit.block.x + normal.x,
+                ray_hit.block.y + normal.y,
+                ray_hit.block.z + normal.z,
+            ));
+
+            // Dither between palette colors for fractional amounts of shading.
+            let shade = (self.sun.shade(light) + self.ambient_occlusion(ray_hit)
+                - light::brightness(block_light))
+            .max(0.0);
+            let whole_shade = shade as u16;
+            whole_shade + dither::dither_fraction(shade - whole_shade as f32, x, y) as u16
+        } else {
+            0
+        };
+
+        (self.texel(ray_hit) as u16 + shading).min(3) + 1
+    }
+
+    // Get the palette color of the texture where a ray hit.
+    fn texel(&self, ray_hit: &RayHit) -> u8 {
+        // Multiply uv by texture size then wrap to stay within the texture's bounds.
+        // "a & (x-1)" is the same as "a % x".
+        let u = (ray_hit.u * TEXTURE_SIZE as f32) as usize & (TEXTURE_SIZE - 1);
+        let v = (ray_hit.v * TEXTURE_SIZE as f32) as usize & (TEXTURE_SIZE - 1);
+
+        let tile = block::get(ray_hit.voxel).texture(ray_hit.face, self.frame_count);
+        texture::sample(tile, u, v)
+    }
+
+    // Find the block that the player would break or place against,
+    // blocks that aren't solid are ignored.
+    fn raycast_solid(&self, start: Vec3<f32>, direction: &Vec3<f32>) -> Option<RayHit> {
+        self.raycast_through(start, direction, INTERACT_DISTANCE, |ray_hit| {
+            !block::get(ray_hit.voxel).solid
+        })
+    }
+
+    fn is_reflective(&self, ray_hit: &RayHit) -> bool {
+        block::get(ray_hit.voxel).reflective && self.texel(ray_hit) == 0
+    }
+
+    // Check if a ray drawing the pixel at x, y can be seen through the voxel it hit.
+    fn is_see_through(&self, ray_hit: &RayHit, x: usize, y: usize) -> bool {
+        match block::get(ray_hit.voxel).opacity {
+            block::Opacity::Opaque => false,
+            block::Opacity::Cutout => self.texel(ray_hit) == 0,
+            // Screen-door transparency, the block only covers some of the pixels.
+            block::Opacity::Translucent(coverage) => !dither::dither(coverage, x, y),
         }
     }
 
+    // Find the voxel that's visible at the pixel x, y along a ray.
+    fn raycast_visible(
+        &self,
+        start: Vec3<f32>,
+        direction: &Vec3<f32>,
+        range: f32,
+        x: usize,
+        y: usize,
+    ) -> Option<RayHit> {
+        self.raycast_through(start, direction, range, |ray_hit| {
+            self.is_see_through(ray_hit, x, y)
+        })
+    }
+
+    // Check if anything blocks the sunlight reaching a hit point,
+    // light passes through the same parts of blocks that can be seen through.
+    fn in_shadow(&self, ray_hit: &RayHit) -> bool {
+        // Move the start of the shadow ray off of the face,
+        // otherwise it would immediately hit the block it started on.
+        let normal = ray_hit.face.normal();
+        let start = Vec3::<f32> {
+            x: ray_hit.point.x + normal.x as f32 * SHADOW_RAY_OFFSET,
+            y: ray_hit.point.y + normal.y as f32 * SHADOW_RAY_OFFSET,
+            z: ray_hit.point.z + normal.z as f32 * SHADOW_RAY_OFFSET,
+        };
+
+        self.raycast_through(
+            start,
+            &self.sun.towards(),
+            self.sun.shadow_range,
+            |shadow_hit| {
+                // Dither by where the ray crosses the block rather than by screen pixel,
+                // so shadows through translucent blocks stay still as the camera moves.
+                let u = (shadow_hit.u * TEXTURE_SIZE as f32) as usize;
+                let v = (shadow_hit.v * TEXTURE_SIZE as f32) as usize;
+                self.is_see_through(shadow_hit, u, v)
+            },
+        )
+        .is_some()
+    }
+
     // Uses DDA Voxel traversal to find the first voxel hit by the ray.
-    fn raycast(
+    pub fn raycast(&self, start: Vec3<f32>, direction: &Vec3<f32>, range: f32) -> Option<RayHit> {
+        self.raycast_through(start, direction, range, |_| false)
+    }
+
+    // Same as raycast, but the ray keeps going through voxels when pass_through returns true.
+    pub fn raycast_through(
         &self,
         mut start: Vec3<f32>,
         direction: &Vec3<f32>,
         range: f32,
-        with_block: bool,
+        mut pass_through: impl FnMut(&RayHit) -> bool,
     ) -> Option<RayHit> {
         // Add a small bias to prevent landing perfectly on block boundaries,
         // otherwise there will be visual glitches in that case.
@@ -323,8 +642,83 @@ impl Game {
 
         let mut hit_block = self.hit_map(&block);
         let mut last_move = 0;
-        while !hit_block && last_dist_to_next < range {
-            if dist_to_next.x < dist_to_next.y && dist_to_next.x < dist_to_next.z {
+        loop {
+            if hit_block {
+                let ray_hit = self.ray_hit(
+                    &start,
+                    direction,
+                    &tile_dir,
+                    block,
+                    last_move,
+                    last_dist_to_next,
+                );
+                if !pass_through(&ray_hit) {
+                    return Some(ray_hit);
+                }
+            }
+
+            if last_dist_to_next >= range {
+                break;
+            }
+
+            #[cfg(test)]
+            self.ray_steps.set(self.ray_steps.get() + 1);
+
+            if self.map.is_brick_empty(&block) {
+                // The ray is inside a brick without any occupied voxels, so jump to where it
+                // leaves the brick. That happens when it reaches the brick's boundary on any axis.
+                let exit_x = Self::brick_exit(block.x, tile_dir.x, dist_to_next.x, ray_step.x);
+                let exit_y = Self::brick_exit(block.y, tile_dir.y, dist_to_next.y, ray_step.y);
+                let exit_z = Self::brick_exit(block.z, tile_dir.z, dist_to_next.z, ray_step.z);
+
+                if exit_x < exit_y && exit_x < exit_z {
+                    last_dist_to_next = exit_x;
+                    last_move = 0;
+                } else if exit_y < exit_z {
+                    last_dist_to_next = exit_y;
+                    last_move = 1;
+                } else {
+                    last_dist_to_next = exit_z;
+                    last_move = 2;
+                }
+
+                if last_dist_to_next >= range {
+                    break;
+                }
+
+                // Take every step that would have been taken before leaving the brick,
+                // adding the steps one at a time keeps the distances identical to normal stepping.
+                while dist_to_next.x < last_dist_to_next {
+                    dist_to_next.x += ray_step.x;
+                    block.x += tile_dir.x;
+                }
+
+                while dist_to_next.y < last_dist_to_next {
+                    dist_to_next.y += ray_step.y;
+                    block.y += tile_dir.y;
+                }
+
+                while dist_to_next.z < last_dist_to_next {
+                    dist_to_next.z += ray_step.z;
+                    block.z += tile_dir.z;
+                }
+
+                // Then take the step out of the brick.
+                match last_move {
+                    0 => {
+                        dist_to_next.x += ray_step.x;
+                        block.x += tile_dir.x;
+                    }
+                    1 => {
+                        dist_to_next.y += ray_step.y;
+                        block.y += tile_dir.y;
+                    }
+                    _ => {
+                        dist_to_next.z += ray_step.z;
+                        block.z += tile_dir.z;
+                    }
+                }
+            } else if dist_to_next.x < dist_to_next.y && dist_to_next.x < dist_to_next.z {
                 last_dist_to_next = dist_to_next.x;
                 dist_to_next.x += ray_step.x;
                 block.x += tile_dir.x;
@@ -342,28 +736,80 @@ impl Game {
             }
 
             hit_block = self.hit_map(&block);
+
+            if !hit_block && self.map.has_left(&block, &tile_dir) {
+                break;
+            }
         }
 
-        if !hit_block {
-            return None;
+        None
+    }
+
+    // Fill in the details of where a raycast hit a voxel.
+    fn ray_hit(
+        &self,
+        start: &Vec3<f32>,
+        direction: &Vec3<f32>,
+        tile_dir: &Vec3<i32>,
+        block: Vec3<i32>,
+        last_move: u8,
+        distance: f32,
+    ) -> RayHit {
+        let face = match last_move {
+            0 => Face::entered_through(0, tile_dir.x),
+            1 => Face::entered_through(1, tile_dir.y),
+            _ => Face::entered_through(2, tile_dir.z),
+        };
+        let point = Vec3::<f32> {
+            x: start.x + distance * direction.x,
+            y: start.y + distance * direction.y,
+            z: start.z + distance * direction.z,
+        };
+
+        // Use the hit point's position within the block to find the uv,
+        // v increases downwards on the sides of blocks so textures appear upright.
+        let local_point = Vec3::<f32> {
+            x: point.x - block.x as f32,
+            y: point.y - block.y as f32,
+            z: point.z - block.z as f32,
+        };
+        let (u, v) = match face.axis() {
+            0 => (local_point.z, local_point.y),
+            1 => (local_point.x, local_point.z),
+            _ => (local_point.x, local_point.y),
+        };
+
+        RayHit {
+            distance,
+            face,
+            point,
+            u,
+            v,
+            block,
+            voxel: self.get_map(&block),
         }
+    }
+}
 
-        Some(RayHit {
-            distance: last_dist_to_next,
-            hit_side: last_move,
-            block: if with_block { Some(block) } else { None },
-        })
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+#[cfg(target_arch = "wasm32")]
 static mut GAME: Game = Game::new();
 
+#[cfg(target_arch = "wasm32")]
 #[no_mangle]
 unsafe fn start() {
-    GAME.start();
+    let game = &mut *std::ptr::addr_of_mut!(GAME);
+    game.start();
+    game.load_settings(&mut Wasm4Platform);
 }
 
+#[cfg(target_arch = "wasm32")]
 #[no_mangle]
 unsafe fn update() {
-    GAME.update(*GAMEPAD1, *GAMEPAD2);
+    (*std::ptr::addr_of_mut!(GAME)).update(&mut Wasm4Platform);
 }
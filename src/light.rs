@@ -0,0 +1,337 @@
+use crate::block;
+use crate::map::{Boundary, Map, MAP_LENGTH, MAP_SIZE};
+
+// Brightest level of block light, it drops by one for each voxel of air it spreads through.
+pub const MAX_LIGHT: u8 = 15;
+// Shading removed from faces lit at MAX_LIGHT, in palette colors.
+const LIGHT_SHADE: f32 = 3.0;
+
+// Capacity of each flood fill queue. Light spreading out from a single voxel, or being darkened
+// around one, only has two layers of the diamond around it queued at once. With room for the two
+// outermost layers that MAX_LIGHT reaches, changes in the open never overflow.
+const QUEUE_SIZE: usize = diamond_layer(MAX_LIGHT) + diamond_layer(MAX_LIGHT - 1);
+
+// Number of voxels at a distance of exactly steps from a voxel, stepping through faces.
+const fn diamond_layer(steps: u8) -> usize {
+    4 * steps as usize * steps as usize + 2
+}
+
+// Fixed size first in first out queue, remembering if anything didn't fit.
+struct Queue<T, const N: usize> {
+    entries: [T; N],
+    head: usize,
+    len: usize,
+    overflowed: bool,
+}
+
+impl<T: Copy, const N: usize> Queue<T, N> {
+    const fn new(empty: T) -> Self {
+        Self {
+            entries: [empty; N],
+            head: 0,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    fn push(&mut self, entry: T) {
+        if self.len == N {
+            self.overflowed = true;
+            return;
+        }
+
+        self.entries[(self.head + self.len) % N] = entry;
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let entry = self.entries[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+// Queue of voxel indices paired with the light level they had before being darkened.
+// Levels are packed into 4 bits each next to the indices, stored one below the level
+// since removing a glowing block darkens from one above MAX_LIGHT.
+struct DarkenQueue {
+    indices: Queue<u16, QUEUE_SIZE>,
+    levels: [u8; QUEUE_SIZE.div_ceil(2)],
+}
+
+impl DarkenQueue {
+    const fn new() -> Self {
+        Self {
+            indices: Queue::new(0),
+            levels: [0; QUEUE_SIZE.div_ceil(2)],
+        }
+    }
+
+    fn push(&mut self, index: u16, light: u8) {
+        // The level goes in the slot that the index is about to fill, if it fits.
+        if self.indices.len < QUEUE_SIZE {
+            let slot = (self.indices.head + self.indices.len) % QUEUE_SIZE;
+            let shift = (slot & 1) << 2;
+            let packed_levels = &mut self.levels[slot >> 1];
+            *packed_levels = ((light - 1) << shift) | (*packed_levels & !(0xf << shift));
+        }
+
+        self.indices.push(index);
+    }
+
+    fn pop(&mut self) -> Option<(u16, u8)> {
+        let slot = self.indices.head;
+        let index = self.indices.pop()?;
+
+        let shift = (slot & 1) << 2;
+        Some((index, ((self.levels[slot >> 1] >> shift) & 0xf) + 1))
+    }
+}
+
+// The flood fill queues are kept with the map, they're too big for the stack.
+pub(crate) struct LightQueues {
+    darken: DarkenQueue,
+    spread: Queue<u16, QUEUE_SIZE>,
+}
+
+impl LightQueues {
+    pub const fn new() -> Self {
+        Self {
+            darken: DarkenQueue::new(),
+            spread: Queue::new(0),
+        }
+    }
+}
+
+// Get how much shading a face in front of a voxel with the given light level loses,
+// squared so that light falls off quickly away from where it's given off.
+pub fn brightness(light: u8) -> f32 {
+    let fraction = light as f32 / MAX_LIGHT as f32;
+    fraction * fraction * LIGHT_SHADE
+}
+
+impl Map {
+    // Update block light after the voxel at index changed, by first darkening everything
+    // that could have been lit through the old voxel and then flood filling light back in
+    // from whatever is still lit around the darkened area.
+    pub(crate) fn update_light(&mut self, index: usize, old_voxel: u8, old_light: u8) {
+        // Air that was filled in takes its light with it, and so does a glowing block
+        // that was removed. Its neighbors were lit one level below it.
+        let removed_light = if old_voxel == block::AIR {
+            old_light
+        } else {
+            block::get(old_voxel).emission + 1
+        };
+        if removed_light > 1 {
+            self.light_queues.darken.push(index as u16, removed_light);
+        }
+        self.darken();
+
+        if self.is_occupied_at(index) {
+            self.light_queues.spread.push(index as u16);
+        } else {
+            // Light flows into new air from every side.
+            for neighbor in self.neighbors(index).into_iter().flatten() {
+                self.light_queues.spread.push(neighbor as u16);
+            }
+        }
+        self.spread();
+    }
+
+    // Switch to another boundary, fixing up the light that starts or stops crossing the map's edges.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        let wraps = boundary == Boundary::Wrap;
+        if (self.boundary == Boundary::Wrap) == wraps {
+            self.boundary = boundary;
+            return;
+        }
+
+        if !wraps {
+            // Darken air lit from the other side of the map while the edges still connect,
+            // along with everything it lit in turn.
+            for index in (0..MAP_LENGTH).filter(|&index| Self::is_on_edge(index)) {
+                let light = self.light_at(index);
+                if light > 0
+                    && self.neighbors(index).into_iter().flatten().any(|neighbor| {
+                        Self::is_across_edge(index, neighbor) && self.light_from(neighbor) >= light
+                    })
+                {
+                    self.set_light_at(index, 0);
+                    self.light_queues.darken.push(index as u16, light);
+                }
+            }
+            self.darken();
+        }
+
+        // Light from the edges spreads across them if they connect now.
+        self.boundary = boundary;
+        for index in (0..MAP_LENGTH).filter(|&index| Self::is_on_edge(index)) {
+            if self.light_from(index) > 0 {
+                self.light_queues.spread.push(index as u16);
+            }
+        }
+        self.spread();
+    }
+
+    // Darken everything lit through the voxels in the darken queue. Voxels next to the darkened area
+    // that are lit some other way are queued up to spread their light back into it.
+    fn darken(&mut self) {
+        loop {
+            while let Some((current, light)) = self.light_queues.darken.pop() {
+                for neighbor in self.neighbors(current as usize).into_iter().flatten() {
+                    if self.is_occupied_at(neighbor) {
+                        // Glowing blocks next to the darkened area need to light it again.
+                        if self.emission_at(neighbor) > 0 {
+                            self.light_queues.spread.push(neighbor as u16);
+                        }
+                        continue;
+                    }
+
+                    let neighbor_light = self.light_at(neighbor);
+                    if neighbor_light == 0 {
+                        continue;
+                    }
+
+                    if neighbor_light < light {
+                        self.set_light_at(neighbor, 0);
+                        self.light_queues
+                            .darken
+                            .push(neighbor as u16, neighbor_light);
+                    } else {
+                        // Lit by something else, so it can spread light back into the darkened area.
+                        self.light_queues.spread.push(neighbor as u16);
+                    }
+                }
+            }
+
+            if !self.light_queues.darken.indices.overflowed {
+                return;
+            }
+
+            // Voxels that were darkened without fitting in the queue left light behind that
+            // nothing lights anymore, and didn't queue their lit neighbors to spread.
+            // Find that light and keep going a queue at a time until it's all gone.
+            self.light_queues.darken.indices.overflowed = false;
+            self.light_queues.spread.overflowed = true;
+            for index in 0..MAP_LENGTH {
+                let light = self.light_at(index);
+                if light > 0 && self.light_from_neighbors(index) < light {
+                    self.set_light_at(index, 0);
+                    self.light_queues.darken.push(index as u16, light);
+                }
+            }
+        }
+    }
+
+    // Flood fill light out from the voxels in the spread queue.
+    fn spread(&mut self) {
+        loop {
+            while let Some(current) = self.light_queues.spread.pop() {
+                let light = self.light_from(current as usize);
+                if light == 0 {
+                    continue;
+                }
+
+                for neighbor in self.neighbors(current as usize).into_iter().flatten() {
+                    if !self.is_occupied_at(neighbor) && self.light_at(neighbor) < light {
+                        self.set_light_at(neighbor, light);
+                        self.light_queues.spread.push(neighbor as u16);
+                    }
+                }
+            }
+
+            if !self.light_queues.spread.overflowed {
+                return;
+            }
+
+            // Voxels that were brightened without fitting in the queue haven't passed their
+            // light on yet. Find them and keep going a queue at a time until it's all spread.
+            self.light_queues.spread.overflowed = false;
+            for index in 0..MAP_LENGTH {
+                let light = self.light_from(index);
+                if light > 0
+                    && self.neighbors(index).into_iter().flatten().any(|neighbor| {
+                        !self.is_occupied_at(neighbor) && self.light_at(neighbor) < light
+                    })
+                {
+                    self.light_queues.spread.push(index as u16);
+                }
+            }
+        }
+    }
+
+    // Recalculate the light of every voxel in the map from scratch.
+    #[cfg(test)]
+    pub(crate) fn relight(&mut self) {
+        for index in 0..MAP_LENGTH {
+            if !self.is_occupied_at(index) {
+                self.set_light_at(index, 0);
+            }
+        }
+
+        // Light only ever gets brighter from here, so keep sweeping back and forth
+        // through the map until it stops changing.
+        loop {
+            let mut changed = false;
+            for index in (0..MAP_LENGTH).chain((0..MAP_LENGTH).rev()) {
+                if self.is_occupied_at(index) {
+                    continue;
+                }
+
+                let light = self.light_from_neighbors(index);
+                if light > self.light_at(index) {
+                    self.set_light_at(index, light);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    // Get the brightest light that the neighbors of a voxel give it.
+    fn light_from_neighbors(&self, index: usize) -> u8 {
+        self.neighbors(index)
+            .into_iter()
+            .flatten()
+            .map(|neighbor| self.light_from(neighbor))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Get the light level a voxel gives to the air next to it.
+    fn light_from(&self, index: usize) -> u8 {
+        if self.is_occupied_at(index) {
+            self.emission_at(index)
+        } else {
+            self.light_at(index).saturating_sub(1)
+        }
+    }
+
+    fn emission_at(&self, index: usize) -> u8 {
+        block::get(self.voxel_at(index)).emission
+    }
+
+    fn is_on_edge(index: usize) -> bool {
+        [
+            index % MAP_SIZE,
+            index / MAP_SIZE % MAP_SIZE,
+            index / (MAP_SIZE * MAP_SIZE),
+        ]
+        .into_iter()
+        .any(|coordinate| coordinate == 0 || coordinate == MAP_SIZE - 1)
+    }
+
+    // Check if two neighboring voxels are only next to each other because the map wraps around.
+    fn is_across_edge(index: usize, neighbor: usize) -> bool {
+        let distance = index.abs_diff(neighbor);
+        distance != 1 && distance != MAP_SIZE && distance != MAP_SIZE * MAP_SIZE
+    }
+}
@@ -0,0 +1,301 @@
+use crate::block;
+use crate::light;
+use crate::vec3::*;
+
+pub const MAP_SIZE: usize = 32;
+pub const MAP_LENGTH: usize = MAP_SIZE * MAP_SIZE * MAP_SIZE;
+// The map is split into bricks of 4x4x4 voxels, which raycasts skip over when they're empty.
+pub const BRICK_SIZE: usize = 4;
+const BRICK_GRID_SIZE: usize = MAP_SIZE / BRICK_SIZE;
+pub const BRICK_COUNT: usize = BRICK_GRID_SIZE * BRICK_GRID_SIZE * BRICK_GRID_SIZE;
+
+// Block ids are packed into 4 bits per voxel.
+const _: () = assert!(block::BLOCK_COUNT <= 16);
+
+// What the world looks like past the edges of the map.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Boundary {
+    // The map repeats forever in every direction.
+    Wrap,
+    // The map is surrounded by air.
+    Air,
+    // The map is enclosed in a solid shell of bedrock.
+    Bedrock,
+}
+
+pub struct Map {
+    // Changed with set_boundary, which keeps block light up to date.
+    pub(crate) boundary: Boundary,
+    // One bit per voxel, set when the voxel isn't air.
+    // This is all that raycasts need to check while stepping through the map.
+    occupancy: [u32; MAP_LENGTH / 32],
+    // Block ids, two voxels per byte.
+    // Air doesn't need an id, so air voxels store their light level instead.
+    blocks: [u8; MAP_LENGTH / 2],
+    // Number of voxels that aren't air in each brick of the map.
+    pub(crate) bricks: [u8; BRICK_COUNT],
+    // Height of the top voxel that isn't air in each column, counted up from the bottom of the map.
+    heights: [u8; MAP_SIZE * MAP_SIZE],
+    // Scratch space for updating block light.
+    pub(crate) light_queues: light::LightQueues,
+}
+
+impl Map {
+    pub const fn new() -> Self {
+        Self {
+            boundary: Boundary::Wrap,
+            occupancy: [0; MAP_LENGTH / 32],
+            blocks: [0; MAP_LENGTH / 2],
+            bricks: [0; BRICK_COUNT],
+            heights: [0; MAP_SIZE * MAP_SIZE],
+            light_queues: light::LightQueues::new(),
+        }
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    // Positions outside of the map wrap around to the other side.
+    fn index(position: &Vec3<i32>) -> usize {
+        let wrapped_position = Vec3::<usize> {
+            x: position.x as usize & (MAP_SIZE - 1),
+            y: position.y as usize & (MAP_SIZE - 1),
+            z: position.z as usize & (MAP_SIZE - 1),
+        };
+
+        wrapped_position.x
+            + wrapped_position.y * MAP_SIZE
+            + wrapped_position.z * MAP_SIZE * MAP_SIZE
+    }
+
+    fn brick_index(position: &Vec3<i32>) -> usize {
+        let x = (position.x as usize & (MAP_SIZE - 1)) / BRICK_SIZE;
+        let y = (position.y as usize & (MAP_SIZE - 1)) / BRICK_SIZE;
+        let z = (position.z as usize & (MAP_SIZE - 1)) / BRICK_SIZE;
+
+        x + y * BRICK_GRID_SIZE + z * BRICK_GRID_SIZE * BRICK_GRID_SIZE
+    }
+
+    fn in_bounds(position: &Vec3<i32>) -> bool {
+        // Negative positions become very large when cast, so they fail the comparison too.
+        (position.x as u32) < MAP_SIZE as u32
+            && (position.y as u32) < MAP_SIZE as u32
+            && (position.z as u32) < MAP_SIZE as u32
+    }
+
+    // Get the block that's outside of the map if a position is out of bounds.
+    fn outside(&self, position: &Vec3<i32>) -> Option<u8> {
+        match self.boundary {
+            Boundary::Wrap => None,
+            _ if Self::in_bounds(position) => None,
+            Boundary::Air => Some(block::AIR),
+            Boundary::Bedrock => Some(block::BEDROCK),
+        }
+    }
+
+    // Check if a ray stepping in tile_dir has left the map for good,
+    // only possible when the map is surrounded by air.
+    pub fn has_left(&self, position: &Vec3<i32>, tile_dir: &Vec3<i32>) -> bool {
+        let size = MAP_SIZE as i32;
+
+        self.boundary == Boundary::Air
+            && ((position.x < 0 && tile_dir.x < 0)
+                || (position.x >= size && tile_dir.x > 0)
+                || (position.y < 0 && tile_dir.y < 0)
+                || (position.y >= size && tile_dir.y > 0)
+                || (position.z < 0 && tile_dir.z < 0)
+                || (position.z >= size && tile_dir.z > 0))
+    }
+
+    pub fn is_occupied(&self, position: &Vec3<i32>) -> bool {
+        if let Some(voxel) = self.outside(position) {
+            return voxel != block::AIR;
+        }
+
+        self.is_occupied_at(Self::index(position))
+    }
+
+    pub fn get(&self, position: &Vec3<i32>) -> u8 {
+        if let Some(voxel) = self.outside(position) {
+            return voxel;
+        }
+
+        self.voxel_at(Self::index(position))
+    }
+
+    pub fn set(&mut self, position: &Vec3<i32>, voxel: u8) {
+        // Only voxels inside of the map can be changed.
+        if self.outside(position).is_some() {
+            return;
+        }
+
+        let index = Self::index(position);
+        let old_voxel = self.voxel_at(index);
+        let old_light = self.light_at(index);
+        if voxel == old_voxel {
+            return;
+        }
+
+        // New air starts out dark, update_light works out how much light reaches it.
+        self.set_nibble(index, voxel);
+
+        let was_occupied = old_voxel != block::AIR;
+        let is_occupied = voxel != block::AIR;
+        if is_occupied != was_occupied {
+            let occupancy_bit = 1 << (index & 31);
+            if is_occupied {
+                self.occupancy[index >> 5] |= occupancy_bit;
+            } else {
+                self.occupancy[index >> 5] &= !occupancy_bit;
+            }
+
+            // Keep track of how many voxels are occupied in each brick,
+            // so raycasts know which bricks are empty and can be skipped.
+            let brick = &mut self.bricks[Self::brick_index(position)];
+            if is_occupied {
+                *brick += 1;
+            } else {
+                *brick -= 1;
+            }
+        }
+
+        if is_ground(voxel) != is_ground(old_voxel) {
+            self.update_height(index);
+        }
+
+        self.update_light(index, old_voxel, old_light);
+    }
+
+    // Get the level of block light in a voxel, voxels that aren't air are always dark.
+    pub fn light(&self, position: &Vec3<i32>) -> u8 {
+        if self.outside(position).is_some() {
+            return 0;
+        }
+
+        self.light_at(Self::index(position))
+    }
+
+    pub fn is_brick_empty(&self, position: &Vec3<i32>) -> bool {
+        if let Some(voxel) = self.outside(position) {
+            return voxel == block::AIR;
+        }
+
+        unsafe { *self.bricks.get_unchecked(Self::brick_index(position)) == 0 }
+    }
+
+    // Get the height of the top ground voxel in a column, 0 if the column has none.
+    // The y axis points down, so a voxel at y is MAP_SIZE - y high.
+    // Columns past the edge of a finite map are either empty or full of bedrock.
+    pub fn height(&self, x: i32, z: i32) -> u8 {
+        match self.outside(&Vec3::new(x, 0, z)) {
+            Some(block::AIR) => return 0,
+            Some(_) => return MAP_SIZE as u8,
+            None => {}
+        }
+
+        let x = x as usize & (MAP_SIZE - 1);
+        let z = z as usize & (MAP_SIZE - 1);
+
+        self.heights[x + z * MAP_SIZE]
+    }
+
+    fn update_height(&mut self, index: usize) {
+        let x = index % MAP_SIZE;
+        let y = index / MAP_SIZE % MAP_SIZE;
+        let z = index / (MAP_SIZE * MAP_SIZE);
+        let column = x + z * MAP_SIZE;
+        let height = self.heights[column];
+        let voxel_height = (MAP_SIZE - y) as u8;
+
+        if is_ground(self.voxel_at(index)) {
+            self.heights[column] = height.max(voxel_height);
+        } else if height == voxel_height {
+            // The top voxel was removed, look for the next one down.
+            let bottom = x + z * MAP_SIZE * MAP_SIZE;
+            self.heights[column] = (y + 1..MAP_SIZE)
+                .find(|&y| is_ground(self.voxel_at(bottom + y * MAP_SIZE)))
+                .map_or(0, |y| (MAP_SIZE - y) as u8);
+        }
+    }
+
+    // Access to voxels by index, for spreading light through the map.
+
+    pub(crate) fn is_occupied_at(&self, index: usize) -> bool {
+        unsafe { *self.occupancy.get_unchecked(index >> 5) & (1 << (index & 31)) != 0 }
+    }
+
+    pub(crate) fn voxel_at(&self, index: usize) -> u8 {
+        if self.is_occupied_at(index) {
+            self.nibble(index)
+        } else {
+            block::AIR
+        }
+    }
+
+    pub(crate) fn light_at(&self, index: usize) -> u8 {
+        if self.is_occupied_at(index) {
+            0
+        } else {
+            self.nibble(index)
+        }
+    }
+
+    // Only air voxels have room to store light.
+    pub(crate) fn set_light_at(&mut self, index: usize, light: u8) {
+        debug_assert!(!self.is_occupied_at(index));
+        self.set_nibble(index, light);
+    }
+
+    // Get the indices of the six voxels sharing a face with a voxel,
+    // None for neighbors past the edge of a finite map.
+    pub(crate) fn neighbors(&self, index: usize) -> [Option<usize>; 6] {
+        let position = Vec3::new(
+            (index % MAP_SIZE) as i32,
+            (index / MAP_SIZE % MAP_SIZE) as i32,
+            (index / (MAP_SIZE * MAP_SIZE)) as i32,
+        );
+        let neighbor = |x: i32, y: i32, z: i32| {
+            let neighbor = Vec3::new(position.x + x, position.y + y, position.z + z);
+            if self.boundary != Boundary::Wrap && !Self::in_bounds(&neighbor) {
+                return None;
+            }
+
+            Some(Self::index(&neighbor))
+        };
+
+        [
+            neighbor(1, 0, 0),
+            neighbor(-1, 0, 0),
+            neighbor(0, 1, 0),
+            neighbor(0, -1, 0),
+            neighbor(0, 0, 1),
+            neighbor(0, 0, -1),
+        ]
+    }
+
+    fn nibble(&self, index: usize) -> u8 {
+        let shift = (index & 1) << 2;
+
+        unsafe { (*self.blocks.get_unchecked(index >> 1) >> shift) & 0xf }
+    }
+
+    fn set_nibble(&mut self, index: usize, value: u8) {
+        let shift = (index & 1) << 2;
+        let packed_voxels = &mut self.blocks[index >> 1];
+        *packed_voxels = (value << shift) | (*packed_voxels & !(0xf << shift));
+    }
+}
+
+// Check if a voxel counts towards the height of its column, only blocks that can be
+// stood on and can't be seen through do, so water, glass, leaves and torches don't.
+fn is_ground(voxel: u8) -> bool {
+    let block = block::get(voxel);
+    block.solid && block.opacity == block::Opacity::Opaque
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
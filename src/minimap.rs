@@ -0,0 +1,91 @@
+use crate::map::MAP_SIZE;
+use crate::platform::{get_framebuffer_pixel, Platform};
+use crate::{Game, SCREEN_WIDTH};
+
+// The minimap shows every column of the map around the camera, one pixel each.
+pub const MINIMAP_SIZE: usize = MAP_SIZE;
+// Distance from the top right corner of the screen.
+const MINIMAP_MARGIN: usize = 3;
+// Length of the line showing which way the camera faces, in pixels.
+const HEADING_LENGTH: f32 = 5.0;
+
+// Top-down heightmap of the area around the camera, drawn over the top right corner.
+pub struct Minimap {
+    pub enabled: bool,
+}
+
+impl Minimap {
+    pub const fn new() -> Self {
+        Self { enabled: false }
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub(crate) fn draw_minimap<P: Platform>(&self, platform: &mut P) {
+        if !self.minimap.enabled {
+            return;
+        }
+
+        let left = SCREEN_WIDTH - MINIMAP_SIZE - MINIMAP_MARGIN;
+        let top = MINIMAP_MARGIN;
+        let center = (MINIMAP_SIZE / 2) as i32;
+        let camera_x = self.camera.position.x.floor() as i32;
+        let camera_z = self.camera.position.z.floor() as i32;
+        let camera_height = MAP_SIZE as f32 - self.camera.position.y;
+
+        // North is up, so z increases towards the top of the minimap.
+        for y in 0..MINIMAP_SIZE {
+            for x in 0..MINIMAP_SIZE {
+                let height = self
+                    .map
+                    .height(camera_x + x as i32 - center, camera_z + center - y as i32)
+                    as f32;
+                // Shade each column by how far its top is below the camera,
+                // darker columns are closer to eye level or above it.
+                let color = match camera_height - height {
+                    depth if depth < 0.0 => 4,
+                    depth if depth < 2.0 => 3,
+                    depth if depth < 6.0 => 2,
+                    _ => 1,
+                };
+
+                platform.set_draw_colors(color);
+                platform.pixel(left + x, top + y);
+            }
+        }
+
+        platform.set_draw_colors(0x40);
+        platform.rect(
+            left as i32 - 1,
+            top as i32 - 1,
+            MINIMAP_SIZE as u32 + 2,
+            MINIMAP_SIZE as u32 + 2,
+        );
+
+        // Draw the heading from the camera's position in the middle of the minimap,
+        // in whichever color stands out from the pixels underneath.
+        let heading_x = self.camera.forward.x;
+        let heading_y = -self.camera.forward.z;
+        let heading_length = (heading_x * heading_x + heading_y * heading_y).sqrt();
+        for step in 0..=HEADING_LENGTH as usize {
+            let (offset_x, offset_y) = if heading_length > 0.0 {
+                let scale = step as f32 / heading_length;
+                (heading_x * scale, heading_y * scale)
+            } else {
+                (0.0, 0.0)
+            };
+            let x = left + (center as f32 + 0.5 + offset_x) as usize;
+            let y = top + (center as f32 + 0.5 + offset_y) as usize;
+            let background = get_framebuffer_pixel(platform.framebuffer(), x, y);
+
+            platform.set_draw_colors(if background < 2 { 4 } else { 1 });
+            platform.pixel(x, y);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::dither;
+use crate::face::Face;
+use crate::projection::Projection;
+use crate::vec3::*;
+use crate::RayHit;
+
+// Width of the outline in pixels, it stays the same no matter how far away the block is.
+const OUTLINE_WIDTH: f32 = 1.5;
+// Fraction of the pixels on the shaded face that get the outline's color.
+const FACE_SHADE_LEVEL: u8 = dither::LEVELS / 2;
+
+// Marks the block that would be broken, or placed against, by the player.
+pub struct Outline {
+    // Shade the face of the targeted block that a new block would be placed on.
+    pub shade_face: bool,
+    target: Option<(Vec3<i32>, Face)>,
+}
+
+impl Outline {
+    pub const fn new() -> Self {
+        Self {
+            shade_face: false,
+            target: None,
+        }
+    }
+
+    pub fn set_target(&mut self, ray_hit: Option<&RayHit>) {
+        self.target = ray_hit.map(|ray_hit| (ray_hit.block, ray_hit.face));
+    }
+
+    // Draw over the color of a pixel if it's on the edge of the targeted block,
+    // using whichever end of the palette contrasts the most with the pixel.
+    pub fn apply(
+        &self,
+        ray_hit: &RayHit,
+        projection: &Projection,
+        color: u16,
+        x: usize,
+        y: usize,
+    ) -> u16 {
+        let face = match self.target {
+            Some((block, face)) if block == ray_hit.block => face,
+            _ => return color,
+        };
+
+        // Convert the outline's width from pixels to a distance across the face.
+        let width = ray_hit.distance * projection.pixel_size() * OUTLINE_WIDTH;
+        let on_edge = ray_hit.u < width
+            || ray_hit.u > 1.0 - width
+            || ray_hit.v < width
+            || ray_hit.v > 1.0 - width;
+        let on_shaded_face =
+            self.shade_face && ray_hit.face == face && dither::dither(FACE_SHADE_LEVEL, x, y);
+
+        if !on_edge && !on_shaded_face {
+            return color;
+        }
+
+        if color >= 3 {
+            1
+        } else {
+            4
+        }
+    }
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,107 @@
+use crate::map::MAP_SIZE;
+use crate::platform::Platform;
+use crate::vec3::*;
+use crate::Game;
+use std::f32::consts::FRAC_1_SQRT_2;
+
+// Isometric view direction, looking down at the map diagonally.
+const VIEW_DIRECTION: Vec3<f32> = Vec3::new(0.577_350_3, 0.577_350_3, 0.577_350_3);
+// Directions in the world that the screen's x and y axes point in.
+const VIEW_RIGHT: Vec3<f32> = Vec3::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2);
+const VIEW_DOWN: Vec3<f32> = Vec3::new(-0.408_248_3, 0.816_496_6, -0.408_248_3);
+// Rays start this far inside of the map, so they don't begin on the edge of a voxel.
+const ENTRY_OFFSET: f32 = 1e-3;
+
+// Orthographic view of the whole map from a fixed isometric angle, for seeing a build at once.
+pub struct Overview {
+    pub enabled: bool,
+    // Half the height of the area shown, in blocks. The default fits the whole map.
+    pub extent: f32,
+}
+
+impl Overview {
+    pub const fn new() -> Self {
+        Self {
+            enabled: false,
+            extent: 28.0,
+        }
+    }
+
+    // Get where the parallel ray through a pixel of the viewport starts,
+    // on the plane through the middle of the map facing the view direction.
+    fn ray_origin(&self, u: f32, v: f32) -> Vec3<f32> {
+        let center = MAP_SIZE as f32 * 0.5;
+
+        Vec3::new(
+            center + (VIEW_RIGHT.x * u + VIEW_DOWN.x * v) * self.extent,
+            center + (VIEW_RIGHT.y * u + VIEW_DOWN.y * v) * self.extent,
+            center + (VIEW_RIGHT.z * u + VIEW_DOWN.z * v) * self.extent,
+        )
+    }
+}
+
+impl Default for Overview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Find the distances along a ray where it enters and leaves the map,
+// None if it misses the map completely.
+fn clip_to_map(origin: &Vec3<f32>, direction: &Vec3<f32>) -> Option<(f32, f32)> {
+    let size = MAP_SIZE as f32;
+    let mut enter = f32::NEG_INFINITY;
+    let mut leave = f32::INFINITY;
+
+    for (origin, direction) in [
+        (origin.x, direction.x),
+        (origin.y, direction.y),
+        (origin.z, direction.z),
+    ] {
+        let near = (0.0 - origin) / direction;
+        let far = (size - origin) / direction;
+        enter = enter.max(near.min(far));
+        leave = leave.min(near.max(far));
+    }
+
+    if enter < leave {
+        Some((enter, leave))
+    } else {
+        None
+    }
+}
+
+impl Game {
+    // Draw the overview into the viewport. Rays are clipped to the map,
+    // so maps that wrap around are still only drawn once.
+    pub(crate) fn draw_overview<P: Platform>(&self, platform: &mut P) {
+        let viewport = self.camera.projection.viewport();
+        let aspect_ratio = viewport.width as f32 / viewport.height as f32;
+
+        for y in viewport.y..viewport.y + viewport.height {
+            for x in viewport.x..viewport.x + viewport.width {
+                let u = ((x - viewport.x) as f32 + 0.5) / viewport.width as f32 * 2.0 - 1.0;
+                let v = ((y - viewport.y) as f32 + 0.5) / viewport.height as f32 * 2.0 - 1.0;
+                let origin = self.overview.ray_origin(u * aspect_ratio, v);
+
+                let color = match clip_to_map(&origin, &VIEW_DIRECTION) {
+                    Some((enter, leave)) => {
+                        let enter = enter + ENTRY_OFFSET;
+                        let start = Vec3::new(
+                            origin.x + VIEW_DIRECTION.x * enter,
+                            origin.y + VIEW_DIRECTION.y * enter,
+                            origin.z + VIEW_DIRECTION.z * enter,
+                        );
+                        // Same as the perspective view, minus the fog and outline.
+                        let (color, _) =
+                            self.trace(start, VIEW_DIRECTION, leave - enter, false, x, y);
+                        color
+                    }
+                    None => self.sky.color(&VIEW_DIRECTION, x, y),
+                };
+                platform.set_draw_colors(color);
+                platform.pixel(x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Number of frames a fade between two themes takes by default.
+const FADE_FRAMES: u32 = 30;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Theme {
+    Default,
+    Sepia,
+    Night,
+    Underwater,
+    HighContrast,
+}
+
+pub const THEMES: [Theme; 5] = [
+    Theme::Default,
+    Theme::Sepia,
+    Theme::Night,
+    Theme::Underwater,
+    Theme::HighContrast,
+];
+
+impl Theme {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::Sepia => "sepia",
+            Theme::Night => "night",
+            Theme::Underwater => "underwater",
+            Theme::HighContrast => "high contrast",
+        }
+    }
+
+    // Palette colors 0-3 as 0xRRGGBB, from lightest to darkest.
+    pub const fn colors(self) -> [u32; 4] {
+        match self {
+            // Same as WASM-4's default palette.
+            Theme::Default => [0xe0f8cf, 0x86c06c, 0x306850, 0x071821],
+            Theme::Sepia => [0xf4e6c8, 0xc4a57a, 0x7a5a3a, 0x2e1f14],
+            Theme::Night => [0x9fb4d9, 0x5c6fa3, 0x2b3566, 0x0b0d24],
+            Theme::Underwater => [0xb8f0e8, 0x4fb0b8, 0x1d5e7a, 0x08203a],
+            Theme::HighContrast => [0xffffff, 0xaaaaaa, 0x555555, 0x000000],
+        }
+    }
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        THEMES.get(index as usize).copied()
+    }
+
+    // Get the next theme, wrapping around after the last one.
+    pub fn next(self) -> Self {
+        THEMES[(self.index() as usize + 1) % THEMES.len()]
+    }
+
+    // Get the previous theme, wrapping around before the first one.
+    pub fn previous(self) -> Self {
+        THEMES[(self.index() as usize + THEMES.len() - 1) % THEMES.len()]
+    }
+}
+
+// The colors written to WASM-4's palette, fading between themes when they change.
+pub struct Palette {
+    theme: Theme,
+    // Colors when the current fade started.
+    from: [u32; 4],
+    colors: [u32; 4],
+    fade_frame: u32,
+    // Number of frames fades take, 0 switches themes instantly.
+    pub fade_frames: u32,
+}
+
+impl Palette {
+    pub const fn new() -> Self {
+        Self {
+            theme: Theme::Default,
+            from: Theme::Default.colors(),
+            colors: Theme::Default.colors(),
+            fade_frame: FADE_FRAMES,
+            fade_frames: FADE_FRAMES,
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn colors(&self) -> [u32; 4] {
+        self.colors
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade_frame < self.fade_frames
+    }
+
+    // Start fading from the current colors to a theme.
+    pub fn set_theme(&mut self, theme: Theme) {
+        if theme == self.theme {
+            return;
+        }
+
+        self.theme = theme;
+        self.from = self.colors;
+        self.fade_frame = 0;
+    }
+
+    // Switch to a theme without fading.
+    pub fn set_theme_instantly(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.colors = theme.colors();
+        self.fade_frame = self.fade_frames;
+    }
+
+    // Advance the fade by a frame.
+    pub fn update(&mut self) {
+        if !self.is_fading() {
+            self.colors = self.theme.colors();
+            return;
+        }
+
+        self.fade_frame += 1;
+        let to = self.theme.colors();
+        for (i, color) in self.colors.iter_mut().enumerate() {
+            *color = Self::lerp_color(self.from[i], to[i], self.fade_frame, self.fade_frames);
+        }
+    }
+
+    // Interpolate each 8 bit channel of two 0xRRGGBB colors by step / steps.
+    fn lerp_color(from: u32, to: u32, step: u32, steps: u32) -> u32 {
+        let mut color = 0;
+
+        for shift in [0, 8, 16] {
+            let from_channel = ((from >> shift) & 0xff) as i32;
+            let to_channel = ((to >> shift) & 0xff) as i32;
+            let channel = from_channel + (to_channel - from_channel) * step as i32 / steps as i32;
+            color |= (channel as u32) << shift;
+        }
+
+        color
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
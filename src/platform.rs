@@ -0,0 +1,119 @@
+use crate::screenshot;
+use crate::wasm4::*;
+
+pub const FRAMEBUFFER_SIZE: usize = 6400;
+
+// Everything the game needs from the console, so that it can run
+// both as a WASM-4 cart and natively on the host for tests and tools.
+pub trait Platform {
+    fn framebuffer(&mut self) -> &mut [u8; FRAMEBUFFER_SIZE];
+    fn palette(&self) -> [u32; 4];
+    fn set_palette(&mut self, palette: [u32; 4]);
+    fn draw_colors(&self) -> u16;
+    fn set_draw_colors(&mut self, draw_colors: u16);
+    // Player index is 0-based, WASM-4 supports up to 4 gamepads.
+    // Players past the last gamepad read the last one.
+    fn gamepad(&self, player: usize) -> u8;
+    fn rect(&mut self, x: i32, y: i32, width: u32, height: u32);
+    fn disk_read(&mut self, dest: &mut [u8]) -> u32;
+    fn disk_write(&mut self, src: &[u8]) -> u32;
+    fn tone(&mut self, frequency: u32, duration: u32, volume: u32, flags: u32);
+    fn trace(&mut self, text: &str);
+
+    // Carts can only get screenshots out through the debug console.
+    fn screenshot(&mut self) {
+        screenshot::trace_screenshot(self);
+    }
+
+    // From the WASM-4 documentation:
+    fn pixel(&mut self, x: usize, y: usize) {
+        let palette_color = (self.draw_colors() & 0xf) as u8;
+        if palette_color == 0 {
+            // Transparent
+            return;
+        }
+
+        set_framebuffer_pixel(self.framebuffer(), x, y, palette_color - 1);
+    }
+}
+
+pub fn set_framebuffer_pixel(
+    framebuffer: &mut [u8; FRAMEBUFFER_SIZE],
+    x: usize,
+    y: usize,
+    color: u8,
+) {
+    // The byte index into the framebuffer that contains (x, y)
+    let idx = (y * SCREEN_SIZE as usize + x) >> 2;
+
+    // Calculate the bits within the byte that corresponds to our position
+    let shift = (x as u8 & 0b11) << 1;
+    let mask = 0b11 << shift;
+
+    let color = color & 0b11;
+    framebuffer[idx] = (color << shift) | (framebuffer[idx] & !mask);
+}
+
+pub fn get_framebuffer_pixel(framebuffer: &[u8; FRAMEBUFFER_SIZE], x: usize, y: usize) -> u8 {
+    let idx = (y * SCREEN_SIZE as usize + x) >> 2;
+    let shift = (x as u8 & 0b11) << 1;
+
+    (framebuffer[idx] >> shift) & 0b11
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct Wasm4Platform;
+
+#[cfg(target_arch = "wasm32")]
+impl Platform for Wasm4Platform {
+    fn framebuffer(&mut self) -> &mut [u8; FRAMEBUFFER_SIZE] {
+        unsafe { &mut *FRAMEBUFFER }
+    }
+
+    fn palette(&self) -> [u32; 4] {
+        unsafe { *PALETTE }
+    }
+
+    fn set_palette(&mut self, palette: [u32; 4]) {
+        unsafe { *PALETTE = palette }
+    }
+
+    fn draw_colors(&self) -> u16 {
+        unsafe { *DRAW_COLORS }
+    }
+
+    fn set_draw_colors(&mut self, draw_colors: u16) {
+        unsafe { *DRAW_COLORS = draw_colors }
+    }
+
+    fn gamepad(&self, player: usize) -> u8 {
+        unsafe {
+            match player {
+                0 => *GAMEPAD1,
+                1 => *GAMEPAD2,
+                2 => *GAMEPAD3,
+                _ => *GAMEPAD4,
+            }
+        }
+    }
+
+    fn rect(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        rect(x, y, width, height);
+    }
+
+    fn disk_read(&mut self, dest: &mut [u8]) -> u32 {
+        unsafe { diskr(dest.as_mut_ptr(), dest.len() as u32) }
+    }
+
+    fn disk_write(&mut self, src: &[u8]) -> u32 {
+        unsafe { diskw(src.as_ptr(), src.len() as u32) }
+    }
+
+    fn tone(&mut self, frequency: u32, duration: u32, volume: u32, flags: u32) {
+        tone(frequency, duration, volume, flags);
+    }
+
+    fn trace(&mut self, text: &str) {
+        trace(text);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::vec3::*;
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+// Distance from the camera to the image plane that rays are cast through.
+const FOCAL_LENGTH: f32 = 1.0;
+
+// Area of the screen that a view is drawn to, in pixels.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Viewport {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Viewport {
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub const fn full_screen() -> Self {
+        Self::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+}
+
+// Turns pixels in the viewport into ray directions relative to the camera, and back again.
+pub struct Projection {
+    // Vertical field of view in radians.
+    fov: f32,
+    // Width of the view divided by its height.
+    aspect_ratio: f32,
+    viewport: Viewport,
+
+    // Size of the image plane, precalculated from the field of view and aspect ratio.
+    width: f32,
+    height: f32,
+    lower_left_corner: Vec3<f32>,
+}
+
+impl Projection {
+    pub const fn new() -> Self {
+        // A 90 degree field of view gives an image plane 2 units tall.
+        Self {
+            fov: std::f32::consts::FRAC_PI_2,
+            aspect_ratio: 1.0,
+            viewport: Viewport::full_screen(),
+            width: 2.0,
+            height: 2.0,
+            lower_left_corner: Vec3::new(-1.0, -1.0, FOCAL_LENGTH),
+        }
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.update();
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+        self.update();
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    // The viewport is clamped to fit on the screen, and to be at least a pixel in size.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        let x = viewport.x.min(SCREEN_WIDTH - 1);
+        let y = viewport.y.min(SCREEN_HEIGHT - 1);
+
+        self.viewport = Viewport::new(
+            x,
+            y,
+            viewport.width.max(1).min(SCREEN_WIDTH - x),
+            viewport.height.max(1).min(SCREEN_HEIGHT - y),
+        );
+    }
+
+    fn update(&mut self) {
+        self.height = 2.0 * FOCAL_LENGTH * (self.fov * 0.5).tan();
+        self.width = self.aspect_ratio * self.height;
+        self.lower_left_corner = Vec3::new(-self.width * 0.5, -self.height * 0.5, FOCAL_LENGTH);
+    }
+
+    // Get the direction of the ray through a pixel on the screen, relative to the camera.
+    // The direction isn't normalized.
+    pub fn ray_direction(&self, x: usize, y: usize) -> Vec3<f32> {
+        let u = (x - self.viewport.x) as f32 / self.viewport.width as f32;
+        let v = (y - self.viewport.y) as f32 / self.viewport.height as f32;
+
+        Vec3::<f32> {
+            x: self.lower_left_corner.x + u * self.width,
+            y: self.lower_left_corner.y + v * self.height,
+            z: self.lower_left_corner.z,
+        }
+    }
+
+    // Get the screen position in pixels of a point relative to the camera,
+    // the point has to be in front of the camera.
+    pub fn project(&self, relative: &Vec3<f32>) -> (f32, f32) {
+        let scale = FOCAL_LENGTH / relative.z;
+        let u = (relative.x * scale - self.lower_left_corner.x) / self.width;
+        let v = (relative.y * scale - self.lower_left_corner.y) / self.height;
+
+        (
+            self.viewport.x as f32 + u * self.viewport.width as f32,
+            self.viewport.y as f32 + v * self.viewport.height as f32,
+        )
+    }
+
+    // Get how many pixels tall something of the given size is at a depth in front of the camera.
+    pub fn projected_size(&self, size: f32, depth: f32) -> f32 {
+        size * FOCAL_LENGTH / depth / self.height * self.viewport.height as f32
+    }
+
+    // Get the size of a pixel at a distance of one unit in front of the camera.
+    pub fn pixel_size(&self) -> f32 {
+        self.height / FOCAL_LENGTH / self.viewport.height as f32
+    }
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
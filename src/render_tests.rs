@@ -0,0 +1,1141 @@
+// Renders fixed camera poses over fixed maps and compares the framebuffer
+// against the golden images in tests/golden.
+// Run with UPDATE_GOLDEN=1 to accept intentional changes to the renderer.
+
+use crate::host::*;
+use crate::platform::*;
+use crate::vec3::*;
+use crate::wasm4::SCREEN_SIZE;
+use crate::*;
+
+const GOLDEN_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const ACTUAL_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/golden");
+
+fn render(game: &mut Game, position: Vec3<f32>, rotation: Vec3<f32>) -> HostPlatform {
+    let mut platform = HostPlatform::new();
+    game.camera.set_pose(position, rotation);
+    game.update(&mut platform);
+    platform
+}
+
+fn check_golden(name: &str, platform: &HostPlatform) {
+    let golden_path = format!("{}/{}.pgm", GOLDEN_DIRECTORY, name);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIRECTORY).unwrap();
+        std::fs::write(&golden_path, encode_pgm(&platform.framebuffer)).unwrap();
+        return;
+    }
+
+    let golden = std::fs::read(&golden_path)
+        .unwrap_or_else(|_| panic!("missing golden image {}", golden_path));
+    let golden =
+        decode_pgm(&golden).unwrap_or_else(|| panic!("invalid golden image {}", golden_path));
+
+    let screen_size = SCREEN_SIZE as usize;
+    let mut different_pixels = 0;
+    for y in 0..screen_size {
+        for x in 0..screen_size {
+            if get_framebuffer_pixel(&golden, x, y) != platform.get_pixel(x, y) {
+                different_pixels += 1;
+            }
+        }
+    }
+
+    if different_pixels > 0 {
+        let actual_path = format!("{}/{}.pgm", ACTUAL_DIRECTORY, name);
+        std::fs::create_dir_all(ACTUAL_DIRECTORY).unwrap();
+        std::fs::write(&actual_path, encode_pgm(&platform.framebuffer)).unwrap();
+        panic!(
+            "{} pixels differ from {}, the actual image was written to {}",
+            different_pixels, golden_path, actual_path
+        );
+    }
+}
+
+fn fill_map(game: &mut Game, min: Vec3<i32>, max: Vec3<i32>, voxel: u8) {
+    for z in min.z..=max.z {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                game.set_map(&Vec3::new(x, y, z), voxel);
+            }
+        }
+    }
+}
+
+// A floor with a few pillars, a wall and an overhang to look at.
+fn build_structures(game: &mut Game) {
+    fill_map(
+        game,
+        Vec3::new(0, 21, 0),
+        Vec3::new(31, 31, 31),
+        block::STONE,
+    );
+    fill_map(
+        game,
+        Vec3::new(0, 20, 0),
+        Vec3::new(31, 20, 31),
+        block::GRASS,
+    );
+    fill_map(game, Vec3::new(4, 15, 8), Vec3::new(4, 19, 8), block::WOOD);
+    fill_map(
+        game,
+        Vec3::new(9, 17, 10),
+        Vec3::new(10, 19, 11),
+        block::DIRT,
+    );
+    fill_map(
+        game,
+        Vec3::new(2, 14, 14),
+        Vec3::new(12, 19, 14),
+        block::PLANKS,
+    );
+    fill_map(
+        game,
+        Vec3::new(6, 14, 11),
+        Vec3::new(8, 14, 13),
+        block::STONE,
+    );
+    fill_map(game, Vec3::new(6, 17, 14), Vec3::new(7, 19, 14), block::AIR);
+}
+
+#[test]
+fn flat_world_default_pose() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    let platform = render(
+        &mut game,
+        Vec3::new(0.0, 15.0, 0.0),
+        Vec3::new(0.0, 0.0, 0.0),
+    );
+    check_golden("flat_world_default_pose", &platform);
+}
+
+#[test]
+fn flat_world_looking_down() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    let platform = render(
+        &mut game,
+        Vec3::new(3.5, 13.2, 5.5),
+        Vec3::new(-0.6, 0.7, 0.0),
+    );
+    check_golden("flat_world_looking_down", &platform);
+}
+
+#[test]
+fn flat_world_negative_coordinates() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    let platform = render(
+        &mut game,
+        Vec3::new(-5.3, 14.1, -9.7),
+        Vec3::new(-0.2, -2.3, 0.0),
+    );
+    check_golden("flat_world_negative_coordinates", &platform);
+}
+
+#[test]
+fn structures() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+    check_golden("structures", &platform);
+}
+
+#[test]
+fn structures_from_above() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    let platform = render(
+        &mut game,
+        Vec3::new(14.5, 11.0, 4.5),
+        Vec3::new(-0.5, -0.6, 0.0),
+    );
+    check_golden("structures_from_above", &platform);
+}
+
+#[test]
+fn structures_without_shadows() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    game.sun.shadows = false;
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+    check_golden("structures_without_shadows", &platform);
+}
+
+#[test]
+fn concave_corner() {
+    let mut game = Box::new(Game::new());
+    fill_map(
+        &mut game,
+        Vec3::new(0, 20, 0),
+        Vec3::new(31, 31, 31),
+        block::STONE,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(4, 16, 10),
+        Vec3::new(12, 19, 10),
+        block::PLANKS,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(12, 16, 4),
+        Vec3::new(12, 19, 10),
+        block::PLANKS,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(10, 19, 8),
+        Vec3::new(11, 19, 9),
+        block::DIRT,
+    );
+    let platform = render(
+        &mut game,
+        Vec3::new(7.5, 17.0, 5.5),
+        Vec3::new(-0.4, 0.8, 0.0),
+    );
+    check_golden("concave_corner", &platform);
+}
+
+#[test]
+fn night_sky() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    game.sky.time_of_day = 0.05;
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.6, 0.1, 0.0),
+    );
+    check_golden("night_sky", &platform);
+}
+
+#[test]
+fn sun_direction() {
+    let mut game = Box::new(Game::new());
+    let mut platform = HostPlatform::new();
+    let sky_direction = game.sky.light_direction();
+    game.update(&mut platform);
+    assert!(game.sun.direction == sky_direction);
+
+    // A direction set on the sun isn't replaced by the day cycle.
+    game.sun.set_direction(Vec3::new(0.0, 2.0, 0.0));
+    game.update(&mut platform);
+    assert!(game.sun.direction == Vec3::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn empty_bricks_are_skipped() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    // Stop time so that both frames are lit the same way.
+    game.sky.day_length = 0;
+    let platform = render(
+        &mut game,
+        Vec3::new(0.0, 15.0, 0.0),
+        Vec3::new(0.0, 0.0, 0.0),
+    );
+    let skipping_steps = game.ray_steps();
+
+    // Marking every brick as occupied makes raycasts step through each voxel.
+    game.map.bricks = [1; map::BRICK_COUNT];
+    let stepping_platform = render(
+        &mut game,
+        Vec3::new(0.0, 15.0, 0.0),
+        Vec3::new(0.0, 0.0, 0.0),
+    );
+    let stepping_steps = game.ray_steps();
+
+    assert!(platform.framebuffer == stepping_platform.framebuffer);
+    assert!(
+        skipping_steps * 2 < stepping_steps,
+        "{} steps with skipping, {} without",
+        skipping_steps,
+        stepping_steps
+    );
+}
+
+#[test]
+fn air_boundary() {
+    let mut game = Box::new(Game::new());
+    game.set_boundary(map::Boundary::Air);
+    game.start();
+    let platform = render(
+        &mut game,
+        Vec3::new(3.5, 14.0, 6.5),
+        Vec3::new(-0.2, -2.0, 0.0),
+    );
+    check_golden("air_boundary", &platform);
+}
+
+#[test]
+fn bedrock_boundary() {
+    let mut game = Box::new(Game::new());
+    game.set_boundary(map::Boundary::Bedrock);
+    game.start();
+    let platform = render(
+        &mut game,
+        Vec3::new(3.5, 14.0, 6.5),
+        Vec3::new(-0.2, -2.0, 0.0),
+    );
+    check_golden("bedrock_boundary", &platform);
+}
+
+#[test]
+fn rays_stop_when_leaving_finite_world() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    game.sky.day_length = 0;
+    render(
+        &mut game,
+        Vec3::new(3.5, 14.0, 6.5),
+        Vec3::new(-0.2, -2.0, 0.0),
+    );
+    let wrapping_steps = game.ray_steps();
+
+    game.set_boundary(map::Boundary::Air);
+    render(
+        &mut game,
+        Vec3::new(3.5, 14.0, 6.5),
+        Vec3::new(-0.2, -2.0, 0.0),
+    );
+    let finite_steps = game.ray_steps();
+
+    assert!(
+        finite_steps < wrapping_steps,
+        "{} steps in a finite world, {} when wrapping",
+        finite_steps,
+        wrapping_steps
+    );
+}
+
+#[test]
+fn see_through_blocks() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    fill_map(
+        &mut game,
+        Vec3::new(6, 17, 14),
+        Vec3::new(7, 19, 14),
+        block::GLASS,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(3, 13, 7),
+        Vec3::new(5, 14, 9),
+        block::LEAVES,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(9, 20, 3),
+        Vec3::new(12, 21, 6),
+        block::WATER,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(10, 21, 4),
+        Vec3::new(11, 21, 5),
+        block::DIRT,
+    );
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 1.3),
+        Vec3::new(-0.1, 0.1, 0.0),
+    );
+    check_golden("see_through_blocks", &platform);
+}
+
+#[test]
+fn fog_covers_far_surfaces() {
+    let mut game = Box::new(Game::new());
+    game.set_map(&Vec3::new(10, 10, 10), block::STONE);
+    // Looking straight at a block 9.5 away.
+    let start = Vec3::new(10.5, 10.5, 0.5);
+    let direction = Vec3::new(0.0, 0.0, 1.0);
+    let range = game.fog.end;
+    let pixels = || (0..4).flat_map(|y| (0..4).map(move |x| (x, y)));
+
+    // Nothing is fogged before the fog starts.
+    assert!(game.fog.start > 9.5);
+    for (x, y) in pixels() {
+        let fogged = game.trace(start, direction, range, true, x, y);
+        let unfogged = game.trace(start, direction, range, false, x, y);
+        assert_eq!(fogged, unfogged);
+    }
+
+    // Past the end, every pixel gets the color of the fog.
+    game.fog.start = 2.0;
+    game.fog.end = 4.0;
+    game.fog.color = Some(7);
+    for (x, y) in pixels() {
+        assert_eq!(game.trace(start, direction, range, true, x, y).0, 7);
+    }
+
+    // Or the color of the sky when the fog has no color.
+    game.fog.color = None;
+    for (x, y) in pixels() {
+        let sky_color = game.sky.color(&direction, x, y);
+        assert_eq!(game.trace(start, direction, range, true, x, y).0, sky_color);
+    }
+}
+
+#[test]
+fn mirrors() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    fill_map(
+        &mut game,
+        Vec3::new(13, 15, 2),
+        Vec3::new(13, 19, 12),
+        block::MIRROR,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(1, 15, 2),
+        Vec3::new(1, 19, 6),
+        block::MIRROR,
+    );
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.0, 0.9, 0.0),
+    );
+    check_golden("mirrors", &platform);
+}
+
+#[test]
+fn reflections_are_bounded() {
+    let mut game = Box::new(Game::new());
+    // Two mirrors facing each other, with the camera between them.
+    fill_map(
+        &mut game,
+        Vec3::new(8, 8, 5),
+        Vec3::new(12, 12, 5),
+        block::MIRROR,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(8, 8, 15),
+        Vec3::new(12, 12, 15),
+        block::MIRROR,
+    );
+    let start = Vec3::new(10.5, 10.5, 10.5);
+    let forward = Vec3::new(0.0, 0.0, 1.0);
+    let backward = Vec3::new(0.0, 0.0, -1.0);
+    let range = 64.0;
+    // Shadow rays would add to the step count.
+    game.sun.shadows = false;
+
+    // Rays bounce straight back and forth, so each bounce lands where a ray
+    // from the start in one direction or the other would have hit.
+    let hits = [
+        game.raycast(start, &forward, range).unwrap(),
+        game.raycast(start, &backward, range).unwrap(),
+    ];
+    assert!(hits.iter().all(|hit| game.is_reflective(hit)));
+
+    let mut steps = Vec::new();
+    for max_reflections in 0..4 {
+        game.max_reflections = max_reflections;
+        game.ray_steps.set(0);
+        let (color, _) = game.trace(start, forward, range, false, 0, 0);
+        steps.push(game.ray_steps());
+
+        // With no reflections left the mirror draws its own texel.
+        let last_hit = &hits[max_reflections as usize % 2];
+        assert_eq!(color, game.surface_color(last_hit, 0, 0));
+    }
+
+    // Every bounce crosses the gap between the mirrors once, then the ray stops.
+    assert!(steps[1] > steps[0]);
+    assert_eq!(steps[2] - steps[1], steps[1] - steps[0]);
+    assert_eq!(steps[3] - steps[2], steps[1] - steps[0]);
+}
+
+#[test]
+fn targeted_block_outline() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    game.outline.shade_face = true;
+    let platform = render(
+        &mut game,
+        Vec3::new(9.8, 17.5, 7.0),
+        Vec3::new(-0.2, 0.0, 0.0),
+    );
+    check_golden("targeted_block_outline", &platform);
+
+    let camera = &game.camera;
+    let position = camera.position;
+    let target = game.raycast_solid(position, &camera.forward).unwrap();
+    let pixels: Vec<(usize, usize)> = (0..SCREEN_SIZE as usize)
+        .flat_map(|y| (0..SCREEN_SIZE as usize).map(move |x| (x, y)))
+        .collect();
+    let directions: Vec<Vec3<f32>> = pixels
+        .iter()
+        .map(|&(x, y)| {
+            let direction = camera
+                .orientation
+                .transform(&camera.projection.ray_direction(x, y));
+            let length =
+                (direction.x * direction.x + direction.y * direction.y + direction.z * direction.z)
+                    .sqrt();
+            Vec3::new(
+                direction.x / length,
+                direction.y / length,
+                direction.z / length,
+            )
+        })
+        .collect();
+    let trace_all = |game: &Game| -> Vec<u16> {
+        pixels
+            .iter()
+            .zip(&directions)
+            .map(|(&(x, y), &direction)| {
+                game.trace(position, direction, game.fog.end, true, x, y).0
+            })
+            .collect()
+    };
+    let outlined = trace_all(&game);
+    game.outline.set_target(None);
+    let plain = trace_all(&game);
+
+    // The outline only draws over pixels showing the block the camera is looking at.
+    let mut outline_pixels = 0;
+    for (i, &(x, y)) in pixels.iter().enumerate() {
+        if outlined[i] != plain[i] {
+            let ray_hit = game
+                .raycast_visible(position, &directions[i], game.fog.end, x, y)
+                .unwrap();
+            assert!(ray_hit.block == target.block);
+            assert!(outlined[i] == 1 || outlined[i] == 4);
+            outline_pixels += 1;
+        }
+    }
+    assert!(outline_pixels > 0);
+}
+
+// Press and release a button on one of the gamepads, a frame each.
+fn press(game: &mut Game, platform: &mut HostPlatform, gamepad: usize, button: u8) {
+    platform.gamepads[gamepad] = button;
+    game.update(platform);
+    platform.gamepads[gamepad] = 0;
+    game.update(platform);
+}
+
+#[test]
+fn extra_players_read_the_last_gamepad() {
+    let mut platform = HostPlatform::new();
+    platform.gamepads[3] = BUTTON_1;
+    assert_eq!(platform.gamepad(3), BUTTON_1);
+    assert_eq!(platform.gamepad(4), BUTTON_1);
+    assert_eq!(platform.gamepad(usize::MAX), BUTTON_1);
+}
+
+#[test]
+fn breaking_and_selecting_blocks() {
+    let mut game = Box::new(Game::new());
+    let block = Vec3::new(10, 10, 10);
+    game.set_map(&block, block::STONE);
+    game.camera
+        .set_pose(Vec3::new(12.5, 10.5, 9.8), Vec3::new(0.0, -1.4, 0.0));
+    let mut platform = HostPlatform::new();
+
+    // Stone takes three hits in a row to break.
+    press(&mut game, &mut platform, 0, BUTTON_1);
+    press(&mut game, &mut platform, 0, BUTTON_1);
+    assert_eq!(game.map.get(&block), block::STONE);
+    press(&mut game, &mut platform, 0, BUTTON_1);
+    assert_eq!(game.map.get(&block), block::AIR);
+
+    // Bedrock never breaks.
+    game.set_map(&block, block::BEDROCK);
+    for _ in 0..5 {
+        press(&mut game, &mut platform, 0, BUTTON_1);
+    }
+    assert_eq!(game.map.get(&block), block::BEDROCK);
+
+    // Button 1 on the second gamepad cycles through every block that can be placed,
+    // back around to stone.
+    let mut selected = Vec::new();
+    for _ in 0..block::BLOCK_COUNT - 2 {
+        press(&mut game, &mut platform, 1, BUTTON_1);
+        selected.push(game.selected_block);
+    }
+    assert_eq!(selected.last(), Some(&block::STONE));
+    assert!(!selected.contains(&block::AIR) && !selected.contains(&block::BEDROCK));
+    selected.sort();
+    selected.dedup();
+    assert_eq!(selected.len(), block::BLOCK_COUNT - 2);
+}
+
+#[test]
+fn place_against_side_face() {
+    let mut game = Box::new(Game::new());
+    let block = Vec3::new(10, 10, 10);
+    game.set_map(&block, block::WOOD);
+    game.camera
+        .set_pose(Vec3::new(12.5, 10.5, 9.8), Vec3::new(0.0, -1.4, 0.0));
+
+    // The camera looks towards -x, at the face of the block pointing towards +x.
+    let ray_hit = game
+        .raycast_solid(game.camera.position, &game.camera.forward)
+        .unwrap();
+    assert!(ray_hit.block == block);
+    assert_eq!(ray_hit.face, face::Face::PosX);
+    assert!(game.camera.forward.x < 0.0);
+
+    let mut platform = HostPlatform::new();
+    platform.gamepads[0] = BUTTON_2;
+    game.update(&mut platform);
+
+    let normal = ray_hit.face.normal();
+    let placed = Vec3::new(block.x + normal.x, block.y + normal.y, block.z + normal.z);
+    assert_eq!(game.map.get(&placed), game.selected_block);
+    assert_eq!(game.map.get(&block), block::WOOD);
+}
+
+#[test]
+fn depth_buffer() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    let platform = render(
+        &mut game,
+        Vec3::new(3.5, 13.2, 5.5),
+        Vec3::new(-0.6, 0.7, 0.0),
+    );
+    let depth_buffer = game.depth_buffer();
+
+    // The sky is left at the far depth.
+    assert_eq!(depth_buffer.get(80, 0), depth::DEPTH_FAR);
+    assert_eq!(depth_buffer.distance(80, 0), None);
+
+    // The center pixel matches a raycast along the camera's forward direction.
+    let ray_hit = game
+        .raycast(game.camera.position, &game.camera.forward, game.fog.end)
+        .unwrap();
+    let distance = depth_buffer.distance(80, 80).unwrap();
+    let step = game.fog.end / depth::DEPTH_FAR as f32;
+    assert!(
+        (distance - ray_hit.distance).abs() < step,
+        "depth buffer has {}, raycast hit at {}",
+        distance,
+        ray_hit.distance
+    );
+    assert!(depth_buffer.is_visible(80, 80, ray_hit.distance - step));
+    assert!(!depth_buffer.is_visible(80, 80, ray_hit.distance + step));
+
+    // The ground gets further away towards the horizon.
+    assert!(depth_buffer.get(80, 159) < depth_buffer.get(80, 120));
+    check_golden("flat_world_looking_down", &platform);
+}
+
+// A stone room under the structures, lit only by a torch and glowstone.
+fn build_cave(game: &mut Game) {
+    build_structures(game);
+    fill_map(
+        game,
+        Vec3::new(1, 13, 0),
+        Vec3::new(14, 20, 13),
+        block::STONE,
+    );
+    fill_map(game, Vec3::new(2, 14, 1), Vec3::new(13, 19, 12), block::AIR);
+    game.set_map(&Vec3::new(10, 19, 8), block::TORCH);
+    game.set_map(&Vec3::new(4, 16, 12), block::GLOWSTONE);
+}
+
+#[test]
+fn block_light() {
+    let mut game = Box::new(Game::new());
+    build_cave(&mut game);
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+    check_golden("block_light", &platform);
+}
+
+#[test]
+fn animated_textures() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    fill_map(
+        &mut game,
+        Vec3::new(5, 20, 4),
+        Vec3::new(8, 20, 7),
+        block::WATER,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(9, 20, 4),
+        Vec3::new(10, 20, 7),
+        block::LAVA,
+    );
+    game.set_map(&Vec3::new(6, 19, 9), block::TORCH);
+
+    let animation = block::get(block::TORCH).animation;
+    assert_eq!(animation.frame(0), 0);
+    assert_eq!(animation.frame(animation.frame_duration as u32), 1);
+    assert_eq!(animation.frame(animation.frame_duration as u32 * 2), 0);
+
+    let position = Vec3::new(7.2, 17.5, 2.3);
+    let rotation = Vec3::new(-0.5, 0.1, 0.0);
+    let first_frame = render(&mut game, position, rotation);
+    game.frame_count = 40;
+    let later_frame = render(&mut game, position, rotation);
+    assert!(first_frame.framebuffer != later_frame.framebuffer);
+    check_golden("animated_textures", &later_frame);
+}
+
+fn light_levels(game: &Game) -> Vec<u8> {
+    let mut levels = Vec::new();
+    for z in 0..MAP_SIZE as i32 {
+        for y in 0..MAP_SIZE as i32 {
+            for x in 0..MAP_SIZE as i32 {
+                levels.push(game.map.light(&Vec3::new(x, y, z)));
+            }
+        }
+    }
+    levels
+}
+
+// Incremental light updates must end up the same as lighting the map from scratch.
+fn check_light(game: &mut Game) {
+    let levels = light_levels(game);
+    game.map.relight();
+    assert!(
+        levels == light_levels(game),
+        "incremental light differs from relighting"
+    );
+}
+
+#[test]
+fn block_light_updates() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+
+    let torch = Vec3::new(20, 17, 20);
+    game.set_map(&torch, block::TORCH);
+    assert_eq!(game.map.light(&Vec3::new(20, 16, 20)), 12);
+    assert_eq!(game.map.light(&Vec3::new(22, 16, 20)), 10);
+    assert_eq!(game.map.light(&Vec3::new(20, 17, 8)), 1);
+    // Light doesn't spread into blocks, or through them.
+    assert_eq!(game.map.light(&Vec3::new(20, 20, 20)), 0);
+    assert_eq!(game.map.light(&Vec3::new(20, 21, 20)), 0);
+    check_light(&mut game);
+
+    // Walling off the torch darkens the other side of the wall.
+    fill_map(
+        &mut game,
+        Vec3::new(18, 14, 18),
+        Vec3::new(18, 19, 22),
+        block::PLANKS,
+    );
+    assert_eq!(game.map.light(&Vec3::new(17, 17, 20)), 4);
+    check_light(&mut game);
+
+    // Opening a hole lets light back through.
+    game.set_map(&Vec3::new(18, 17, 20), block::AIR);
+    assert_eq!(game.map.light(&Vec3::new(17, 17, 20)), 10);
+    check_light(&mut game);
+
+    // Glowstone lights everything in reach.
+    game.set_map(&Vec3::new(16, 18, 16), block::GLOWSTONE);
+    assert_eq!(game.map.light(&Vec3::new(16, 17, 16)), 15);
+    check_light(&mut game);
+
+    game.set_map(&torch, block::AIR);
+    game.set_map(&Vec3::new(16, 18, 16), block::STONE);
+    assert!(light_levels(&game).iter().all(|&level| level == 0));
+}
+
+#[test]
+fn boundary_light() {
+    let mut game = Box::new(Game::new());
+    // Glowstone walls along two sides of the map light the far sides through the edges,
+    // more voxels than fit in the flood fill queues at once.
+    fill_map(
+        &mut game,
+        Vec3::new(0, 0, 0),
+        Vec3::new(0, 31, 31),
+        block::GLOWSTONE,
+    );
+    fill_map(
+        &mut game,
+        Vec3::new(0, 0, 0),
+        Vec3::new(31, 31, 0),
+        block::GLOWSTONE,
+    );
+    assert_eq!(game.map.light(&Vec3::new(31, 15, 15)), 15);
+    check_light(&mut game);
+
+    // Light stops crossing the edges of a finite map.
+    game.set_boundary(map::Boundary::Air);
+    assert_eq!(game.map.boundary(), map::Boundary::Air);
+    assert_eq!(game.map.light(&Vec3::new(31, 15, 15)), 1);
+    check_light(&mut game);
+
+    game.set_boundary(map::Boundary::Wrap);
+    assert_eq!(game.map.light(&Vec3::new(31, 15, 15)), 15);
+    check_light(&mut game);
+}
+
+#[test]
+fn projected_points_match_rays() {
+    let mut game = Box::new(Game::new());
+    game.camera
+        .set_pose(Vec3::new(3.5, 13.2, 5.5), Vec3::new(-0.6, 0.7, 0.0));
+    let camera = &game.camera;
+
+    let forward = Vec3::new(
+        camera.position.x + camera.forward.x * 3.0,
+        camera.position.y + camera.forward.y * 3.0,
+        camera.position.z + camera.forward.z * 3.0,
+    );
+    let projected = camera.project(&forward).unwrap();
+    assert!((projected.x - 80.0).abs() < 1e-3 && (projected.y - 80.0).abs() < 1e-3);
+    assert!((projected.z - 3.0).abs() < 1e-3);
+
+    let behind = Vec3::new(
+        camera.position.x - camera.forward.x,
+        camera.position.y - camera.forward.y,
+        camera.position.z - camera.forward.z,
+    );
+    assert!(camera.project(&behind).is_none());
+}
+
+fn add_sprites(game: &mut Game) {
+    // Partly hidden behind the wood pillar.
+    game.sprites.add(sprite::Sprite::new(
+        Vec3::new(5.1, 19.5, 10.0),
+        1.0,
+        sprite::SLIME,
+    ));
+    game.sprites.add(sprite::Sprite::new(
+        Vec3::new(8.5, 18.8, 6.0),
+        1.5,
+        sprite::PLAYER,
+    ));
+    let gem = game
+        .sprites
+        .add(sprite::Sprite::new(
+            Vec3::new(7.0, 17.0, 8.0),
+            0.5,
+            sprite::GEM,
+        ))
+        .unwrap();
+    game.sprites.get_mut(gem).unwrap().draw_colors = 0x2140;
+}
+
+#[test]
+fn sprites() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    add_sprites(&mut game);
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+    check_golden("sprites", &platform);
+}
+
+#[test]
+fn camera_roll() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    add_sprites(&mut game);
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.4),
+    );
+    check_golden("camera_roll", &platform);
+}
+
+#[test]
+fn roll_keeps_forward() {
+    let mut camera = camera::Camera::new();
+    camera.set_pose(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.3, -1.2, 0.0));
+    let forward = camera.forward;
+    let right = camera.right;
+
+    camera.set_pose(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.3, -1.2, 0.5));
+    assert!((camera.forward.x - forward.x).abs() < 1e-6);
+    assert!((camera.forward.y - forward.y).abs() < 1e-6);
+    assert!((camera.forward.z - forward.z).abs() < 1e-6);
+
+    // Right is turned around the forward axis by the roll.
+    assert!((camera.right.dot(&right) - 0.5f32.cos()).abs() < 1e-5);
+    assert!(camera.right.dot(&camera.forward).abs() < 1e-5);
+}
+
+#[test]
+fn default_projection_matches_fov() {
+    let default_projection = projection::Projection::new();
+    let mut projection = projection::Projection::new();
+    projection.set_fov(default_projection.fov());
+
+    for (x, y) in [(0, 0), (80, 80), (159, 37)] {
+        let expected = default_projection.ray_direction(x, y);
+        let direction = projection.ray_direction(x, y);
+        assert!((direction.x - expected.x).abs() < 1e-5);
+        assert!((direction.y - expected.y).abs() < 1e-5);
+        assert!((direction.z - expected.z).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn zoomed_in() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    game.camera.projection.set_fov(0.5);
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+    check_golden("zoomed_in", &platform);
+}
+
+#[test]
+fn split_viewport() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    game.camera
+        .projection
+        .set_viewport(projection::Viewport::new(0, 80, 160, 80));
+    game.camera.projection.set_aspect_ratio(2.0);
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+
+    // Nothing is drawn outside of the viewport.
+    for y in 0..80 {
+        for x in 0..SCREEN_SIZE as usize {
+            assert_eq!(platform.get_pixel(x, y), 0);
+        }
+    }
+    check_golden("split_viewport", &platform);
+}
+
+#[test]
+fn viewport_is_clamped_to_screen() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    game.camera
+        .projection
+        .set_viewport(projection::Viewport::new(100, 150, 200, 0));
+    assert_eq!(
+        game.camera.projection.viewport(),
+        projection::Viewport::new(100, 150, 60, 1)
+    );
+
+    game.camera
+        .projection
+        .set_viewport(projection::Viewport::new(200, 0, 10, 300));
+    assert_eq!(
+        game.camera.projection.viewport(),
+        projection::Viewport::new(159, 0, 1, 160)
+    );
+
+    // Drawing stays inside of the framebuffer and depth buffer.
+    render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+}
+
+#[test]
+fn palette_fades_between_themes() {
+    let mut palette = palette::Palette::new();
+    palette.fade_frames = 4;
+    palette.set_theme(palette::Theme::HighContrast);
+
+    palette.update();
+    palette.update();
+    // Halfway between 0xe0f8cf and 0xffffff.
+    assert_eq!(palette.colors()[0], 0xeffbe7);
+    assert!(palette.is_fading());
+
+    palette.update();
+    palette.update();
+    assert_eq!(palette.colors(), palette::Theme::HighContrast.colors());
+    assert!(!palette.is_fading());
+}
+
+#[test]
+fn theme_is_saved_and_loaded() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    let mut platform = HostPlatform::new();
+    game.update(&mut platform);
+    assert!(platform.disk.is_empty());
+
+    // Hold button 2 on the second gamepad and press right twice.
+    for _ in 0..2 {
+        platform.gamepads[1] = wasm4::BUTTON_2 | wasm4::BUTTON_RIGHT;
+        game.update(&mut platform);
+        platform.gamepads[1] = wasm4::BUTTON_2;
+        game.update(&mut platform);
+    }
+    assert_eq!(game.palette.theme(), palette::Theme::Night);
+    // The shortcut doesn't move the camera.
+    assert!(game.camera.position == camera::Camera::new().position);
+
+    for _ in 0..palette::Palette::new().fade_frames {
+        game.update(&mut platform);
+    }
+    assert_eq!(platform.palette, palette::Theme::Night.colors());
+
+    let mut loaded_game = Box::new(Game::new());
+    loaded_game.load_settings(&mut platform);
+    assert_eq!(loaded_game.settings(), game.settings());
+
+    // Anything that isn't saved settings is ignored.
+    platform.disk = vec![1, 2, 3];
+    let mut loaded_game = Box::new(Game::new());
+    loaded_game.load_settings(&mut platform);
+    assert_eq!(loaded_game.palette.theme(), palette::Theme::Default);
+}
+
+#[test]
+fn screenshot_shortcut() {
+    let mut game = Box::new(Game::new());
+    game.start();
+    let mut platform = render(
+        &mut game,
+        Vec3::new(3.5, 13.2, 5.5),
+        Vec3::new(-0.6, 0.7, 0.0),
+    );
+    assert!(platform.screenshots.is_empty());
+
+    let directory = std::path::PathBuf::from(ACTUAL_DIRECTORY).join("screenshots");
+    std::fs::create_dir_all(&directory).unwrap();
+    platform.screenshot_directory = Some(directory.clone());
+    platform.gamepads[1] = BUTTON_2 | BUTTON_1;
+    game.update(&mut platform);
+
+    // The shortcut doesn't also change the selected block.
+    assert_eq!(game.selected_block, block::STONE);
+    assert_eq!(platform.screenshots.len(), 1);
+    assert!(platform.screenshots[0] == platform.framebuffer);
+    assert!(platform.last_screenshot_error.is_none());
+    let saved = std::fs::read(directory.join("screenshot_1.pgm")).unwrap();
+    assert!(decode_pgm(&saved) == Some(platform.framebuffer));
+
+    // A screenshot that can't be saved keeps the error instead.
+    platform.screenshot_directory = Some(directory.join("missing"));
+    platform.gamepads[1] = 0;
+    game.update(&mut platform);
+    platform.gamepads[1] = BUTTON_2 | BUTTON_1;
+    game.update(&mut platform);
+    assert_eq!(platform.screenshots.len(), 2);
+    assert!(platform.last_screenshot_error.is_some());
+}
+
+#[test]
+fn traced_screenshot() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    let mut platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+
+    crate::screenshot::trace_screenshot(&mut platform);
+    assert!(platform.traces.iter().all(|line| line.len() <= 600));
+    assert!(decode_traced_screenshot(&platform.traces) == Some(platform.framebuffer));
+}
+
+#[test]
+fn minimap() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    assert_eq!(game.map.height(0, 0), 12);
+    assert_eq!(game.map.height(4, 8), 17);
+    assert_eq!(game.map.height(7, 14), 18);
+
+    // Heights follow blocks being added and removed.
+    game.set_map(&Vec3::new(20, 10, 20), block::STONE);
+    assert_eq!(game.map.height(20, 20), 22);
+    game.set_map(&Vec3::new(20, 10, 20), block::AIR);
+    assert_eq!(game.map.height(20, 20), 12);
+
+    // Only blocks that can be stood on and can't be seen through count.
+    for voxel in [block::WATER, block::GLASS, block::LEAVES, block::TORCH] {
+        game.set_map(&Vec3::new(20, 10, 20), voxel);
+        assert_eq!(game.map.height(20, 20), 12);
+    }
+    game.set_map(&Vec3::new(20, 10, 20), block::MIRROR);
+    assert_eq!(game.map.height(20, 20), 22);
+    game.set_map(&Vec3::new(20, 10, 20), block::WATER);
+    assert_eq!(game.map.height(20, 20), 12);
+    game.set_map(&Vec3::new(20, 10, 20), block::AIR);
+    fill_map(
+        &mut game,
+        Vec3::new(21, 0, 21),
+        Vec3::new(21, 31, 21),
+        block::AIR,
+    );
+    assert_eq!(game.map.height(21, 21), 0);
+
+    // The shortcut toggles the minimap.
+    let mut platform = HostPlatform::new();
+    platform.gamepads[1] = BUTTON_2 | BUTTON_UP;
+    game.update(&mut platform);
+    assert!(game.minimap.enabled);
+
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.5, 0.0),
+    );
+    check_golden("minimap", &platform);
+}
+
+#[test]
+fn overview() {
+    let mut game = Box::new(Game::new());
+    build_structures(&mut game);
+    fill_map(
+        &mut game,
+        Vec3::new(20, 18, 20),
+        Vec3::new(24, 19, 24),
+        block::GLASS,
+    );
+    game.set_map(&Vec3::new(16, 19, 24), block::TORCH);
+
+    // The shortcut toggles the overview, which doesn't depend on the camera.
+    let mut platform = HostPlatform::new();
+    platform.gamepads[1] = BUTTON_2 | BUTTON_DOWN;
+    game.update(&mut platform);
+    assert!(game.overview.enabled);
+
+    let platform = render(
+        &mut game,
+        Vec3::new(7.2, 17.5, 2.3),
+        Vec3::new(0.15, 0.1, 0.0),
+    );
+    check_golden("overview", &platform);
+    let other_pose = render(
+        &mut game,
+        Vec3::new(-5.3, 14.1, -9.7),
+        Vec3::new(-0.2, -2.3, 0.0),
+    );
+    assert!(platform.framebuffer == other_pose.framebuffer);
+}
@@ -0,0 +1,51 @@
+use crate::platform::{Platform, FRAMEBUFFER_SIZE};
+
+// Traced screenshots are the framebuffer's bytes in base64, between these two lines.
+pub const SCREENSHOT_BEGIN: &str = "screenshot 160x160 2bpp base64";
+pub const SCREENSHOT_END: &str = "screenshot end";
+// Framebuffer bytes encoded on each traced line, every line is padded on its own.
+pub const LINE_BYTES: usize = 400;
+const LINE_COUNT: usize = FRAMEBUFFER_SIZE.div_ceil(LINE_BYTES);
+const LINE_LENGTH: usize = LINE_BYTES.div_ceil(3) * 4;
+
+pub const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Write the framebuffer to the debug console a line at a time,
+// so a screenshot can be copied out of a running cart without allocating.
+pub fn trace_screenshot<P: Platform + ?Sized>(platform: &mut P) {
+    platform.trace(SCREENSHOT_BEGIN);
+
+    let mut line = [0; LINE_LENGTH];
+    for index in 0..LINE_COUNT {
+        let start = index * LINE_BYTES;
+        let end = (start + LINE_BYTES).min(FRAMEBUFFER_SIZE);
+        let text = encode_base64(&platform.framebuffer()[start..end], &mut line);
+        platform.trace(text);
+    }
+
+    platform.trace(SCREENSHOT_END);
+}
+
+// Encode bytes as padded base64 into the output buffer, which has to be big enough.
+fn encode_base64<'a>(bytes: &[u8], output: &'a mut [u8]) -> &'a str {
+    let mut length = 0;
+
+    for group in bytes.chunks(3) {
+        let packed = (group[0] as u32) << 16
+            | (*group.get(1).unwrap_or(&0) as u32) << 8
+            | *group.get(2).unwrap_or(&0) as u32;
+
+        for i in 0..4 {
+            output[length + i] = if i <= group.len() {
+                BASE64_ALPHABET[(packed >> (18 - i * 6)) as usize & 0x3f]
+            } else {
+                b'='
+            };
+        }
+        length += 4;
+    }
+
+    // Only ASCII was written, and validating it would pull UTF-8 tables into the cart.
+    unsafe { std::str::from_utf8_unchecked(&output[..length]) }
+}
@@ -0,0 +1,49 @@
+use crate::palette::Theme;
+
+// Saved settings start with these bytes, anything else on the disk is ignored.
+const SETTINGS_MAGIC: [u8; 2] = *b"VX";
+const SETTINGS_VERSION: u8 = 1;
+pub const SETTINGS_SIZE: usize = 4;
+
+// Player preferences that are kept in WASM-4's persistent storage.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Settings {
+    pub theme: Theme,
+}
+
+impl Settings {
+    pub const fn new() -> Self {
+        Self {
+            theme: Theme::Default,
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; SETTINGS_SIZE] {
+        [
+            SETTINGS_MAGIC[0],
+            SETTINGS_MAGIC[1],
+            SETTINGS_VERSION,
+            self.theme.index(),
+        ]
+    }
+
+    // Returns None if the bytes aren't settings saved by this version of the cart.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < SETTINGS_SIZE
+            || bytes[0..2] != SETTINGS_MAGIC
+            || bytes[2] != SETTINGS_VERSION
+        {
+            return None;
+        }
+
+        Some(Self {
+            theme: Theme::from_index(bytes[3])?,
+        })
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,118 @@
+use crate::dither;
+use crate::vec3::*;
+
+// Cosine of the angular radius of the sun and moon discs.
+const SUN_SIZE_COS: f32 = 0.996;
+const MOON_SIZE_COS: f32 = 0.997;
+// Tilt the path of the sun and moon, so they don't pass directly overhead.
+const ORBIT_TILT: f32 = 0.3;
+// Number of star cells around the sky, and how many in 256 of them contain a star.
+const STAR_CELLS: f32 = 160.0;
+const STAR_CHANCE: u32 = 3;
+// Shade at the horizon during the day and night, in palette colors.
+const DAY_SHADE: f32 = 0.0;
+const NIGHT_SHADE: f32 = 2.0;
+// How much darker the sky gets from the horizon up to the zenith.
+const GRADIENT_SHADE: f32 = 1.25;
+
+pub struct Sky {
+    // 0.0 is midnight, 0.25 is sunrise, 0.5 is noon and 0.75 is sunset.
+    pub time_of_day: f32,
+    // Number of frames in a full day, 0 stops time from passing.
+    pub day_length: u32,
+}
+
+impl Sky {
+    pub const fn new() -> Self {
+        Self {
+            time_of_day: 0.45,
+            day_length: 36000,
+        }
+    }
+
+    pub fn update(&mut self) {
+        if self.day_length == 0 {
+            return;
+        }
+
+        self.time_of_day = (self.time_of_day + 1.0 / self.day_length as f32).fract();
+    }
+
+    // Normalized direction pointing towards the sun, the moon is always opposite to it.
+    pub fn towards_sun(&self) -> Vec3<f32> {
+        let angle = (self.time_of_day - 0.25) * std::f32::consts::TAU;
+        let length = (1.0 + ORBIT_TILT * ORBIT_TILT).sqrt();
+
+        // The world's y axis points down, so the sun is above the horizon when y is negative.
+        Vec3::new(
+            angle.cos() / length,
+            -angle.sin() / length,
+            ORBIT_TILT / length,
+        )
+    }
+
+    // Direction that light travels in, from the sun during the day and the moon at night.
+    pub fn light_direction(&self) -> Vec3<f32> {
+        let towards_sun = self.towards_sun();
+
+        if towards_sun.y < 0.0 {
+            Vec3::new(-towards_sun.x, -towards_sun.y, -towards_sun.z)
+        } else {
+            towards_sun
+        }
+    }
+
+    // 1.0 during the day and 0.0 at night, blending between them while the sun is near the horizon.
+    pub fn daylight(&self) -> f32 {
+        (0.5 - self.towards_sun().y * 4.0).clamp(0.0, 1.0)
+    }
+
+    // Get the palette color of the sky in the direction of a ray that hit nothing.
+    pub fn color(&self, direction: &Vec3<f32>, x: usize, y: usize) -> u16 {
+        let towards_sun = self.towards_sun();
+        let facing_sun =
+            direction.x * towards_sun.x + direction.y * towards_sun.y + direction.z * towards_sun.z;
+
+        if facing_sun > SUN_SIZE_COS {
+            return 1;
+        }
+
+        if -facing_sun > MOON_SIZE_COS {
+            return 2;
+        }
+
+        let daylight = self.daylight();
+        let elevation = (-direction.y).max(0.0);
+
+        if daylight < 0.5 && elevation > 0.0 && Self::is_star(direction) {
+            return 2;
+        }
+
+        // Dither a gradient from the horizon up to the zenith.
+        let shade =
+            DAY_SHADE * daylight + NIGHT_SHADE * (1.0 - daylight) + GRADIENT_SHADE * elevation;
+        let whole_shade = shade as u16;
+        let shade = whole_shade + dither::dither_fraction(shade - whole_shade as f32, x, y) as u16;
+
+        shade.min(3) + 1
+    }
+
+    // Stars are placed in cells fixed to the sky, so they move with the camera.
+    fn is_star(direction: &Vec3<f32>) -> bool {
+        let cell_x = (direction.x * STAR_CELLS).floor() as i32 as u32;
+        let cell_y = (direction.y * STAR_CELLS).floor() as i32 as u32;
+        let cell_z = (direction.z * STAR_CELLS).floor() as i32 as u32;
+
+        let hash = cell_x.wrapping_mul(73856093)
+            ^ cell_y.wrapping_mul(19349663)
+            ^ cell_z.wrapping_mul(83492791);
+
+        (hash >> 8) & 0xff < STAR_CHANCE
+    }
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self::new()
+    }
+}
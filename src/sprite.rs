@@ -0,0 +1,188 @@
+use crate::platform::Platform;
+use crate::texture::{sample_atlas, TEXTURE_SIZE, TILE_BYTES};
+use crate::vec3::*;
+use crate::Game;
+
+pub const MAX_SPRITES: usize = 16;
+
+// Image ids into the sprite atlas:
+pub const PLAYER: u8 = 0;
+pub const SLIME: u8 = 1;
+pub const GEM: u8 = 2;
+
+const IMAGE_COUNT: usize = 3;
+
+// 8x8 images in the same 2bpp format as the texture atlas.
+#[rustfmt::skip]
+const ATLAS: [u8; IMAGE_COUNT * TILE_BYTES] = [
+    // Player
+    0b00_00_11_11, 0b11_11_00_00,
+    0b00_00_11_10, 0b10_11_00_00,
+    0b00_00_11_11, 0b11_11_00_00,
+    0b00_11_01_01, 0b01_01_11_00,
+    0b11_00_01_01, 0b01_01_00_11,
+    0b00_00_01_01, 0b01_01_00_00,
+    0b00_00_10_00, 0b00_10_00_00,
+    0b00_00_10_00, 0b00_10_00_00,
+    // Slime
+    0b00_00_00_00, 0b00_00_00_00,
+    0b00_00_00_00, 0b00_00_00_00,
+    0b00_00_01_01, 0b01_01_00_00,
+    0b00_01_01_01, 0b01_01_01_00,
+    0b01_01_11_01, 0b01_11_01_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    0b00_01_01_01, 0b01_01_01_00,
+    // Gem
+    0b00_00_00_00, 0b00_00_00_00,
+    0b00_00_00_11, 0b11_00_00_00,
+    0b00_00_11_10, 0b10_11_00_00,
+    0b00_11_10_01, 0b01_10_11_00,
+    0b00_11_10_01, 0b01_10_11_00,
+    0b00_00_11_10, 0b10_11_00_00,
+    0b00_00_00_11, 0b11_00_00_00,
+    0b00_00_00_00, 0b00_00_00_00,
+];
+
+// An entity in the world drawn as an image that always faces the camera.
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    // Center of the sprite in the world.
+    pub position: Vec3<f32>,
+    // Width and height of the sprite in blocks.
+    pub size: f32,
+    pub image: u8,
+    // Maps the image's palette colors to draw colors like WASM-4's blit,
+    // the lowest nibble is used for palette color 0 and a nibble of 0 is transparent.
+    pub draw_colors: u16,
+}
+
+impl Sprite {
+    pub const fn new(position: Vec3<f32>, size: f32, image: u8) -> Self {
+        Self {
+            position,
+            size,
+            image,
+            draw_colors: 0x4320,
+        }
+    }
+}
+
+pub struct Sprites {
+    sprites: [Option<Sprite>; MAX_SPRITES],
+}
+
+impl Sprites {
+    pub const fn new() -> Self {
+        Self {
+            sprites: [None; MAX_SPRITES],
+        }
+    }
+
+    // Returns the sprite's index, or None if there's no room for more sprites.
+    pub fn add(&mut self, sprite: Sprite) -> Option<usize> {
+        let index = self.sprites.iter().position(|slot| slot.is_none())?;
+        self.sprites[index] = Some(sprite);
+        Some(index)
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.sprites[index] = None;
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Sprite> {
+        self.sprites[index].as_mut()
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites = [None; MAX_SPRITES];
+    }
+}
+
+impl Default for Sprites {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    // Draw every sprite over the voxel scene, sprites write to the depth buffer as they go
+    // so they hide each other and parts of sprites behind blocks are hidden.
+    pub(crate) fn draw_sprites<P: Platform>(&mut self, platform: &mut P) {
+        for index in 0..MAX_SPRITES {
+            if let Some(sprite) = self.sprites.sprites[index] {
+                self.draw_sprite(platform, &sprite);
+            }
+        }
+    }
+
+    fn draw_sprite<P: Platform>(&mut self, platform: &mut P, sprite: &Sprite) {
+        let center = match self.camera.project(&sprite.position) {
+            Some(center) => center,
+            None => return,
+        };
+
+        // The depth buffer holds the distance along each ray, so every pixel of the sprite
+        // is tested using the distance to its center.
+        let offset = Vec3::new(
+            sprite.position.x - self.camera.position.x,
+            sprite.position.y - self.camera.position.y,
+            sprite.position.z - self.camera.position.z,
+        );
+        let distance = (offset.x * offset.x + offset.y * offset.y + offset.z * offset.z).sqrt();
+        let size = self.camera.projection.projected_size(sprite.size, center.z);
+        // Sprites stay upright in the world, so they're rotated on screen when the camera rolls.
+        let (roll_sin, roll_cos) = self.camera.rotation.z.sin_cos();
+        // Half the size of the square on screen that contains the rotated sprite.
+        let extent = size * 0.5 * (roll_sin.abs() + roll_cos.abs());
+        let left = center.x - extent;
+        let top = center.y - extent;
+
+        // Only draw the part of the sprite inside of the viewport.
+        let viewport = self.camera.projection.viewport();
+        let viewport_left = viewport.x as f32;
+        let viewport_top = viewport.y as f32;
+        let viewport_right = (viewport.x + viewport.width) as f32;
+        let viewport_bottom = (viewport.y + viewport.height) as f32;
+        // Avoid clamp, its check for valid bounds pulls float formatting into the cart.
+        let min_x = left.max(viewport_left).min(viewport_right) as usize;
+        let min_y = top.max(viewport_top).min(viewport_bottom) as usize;
+        let max_x = (left + extent * 2.0).max(viewport_left).min(viewport_right) as usize;
+        let max_y = (top + extent * 2.0).max(viewport_top).min(viewport_bottom) as usize;
+
+        for y in min_y..max_y {
+            let offset_y = y as f32 - center.y;
+            for x in min_x..max_x {
+                // Undo the roll to find where the pixel is on the upright sprite.
+                let offset_x = x as f32 - center.x;
+                let u = (offset_x * roll_cos - offset_y * roll_sin) / size + 0.5;
+                let v = (offset_x * roll_sin + offset_y * roll_cos) / size + 0.5;
+                // Pixels less than one outside of the sprite are clamped onto its edge,
+                // only the corners of the square left uncovered by the roll are skipped.
+                let margin = 1.0 / size;
+                if u <= -margin || v <= -margin || u >= 1.0 + margin || v >= 1.0 + margin {
+                    continue;
+                }
+
+                let color = sample_atlas(
+                    &ATLAS,
+                    sprite.image,
+                    ((u * TEXTURE_SIZE as f32) as usize).min(TEXTURE_SIZE - 1),
+                    ((v * TEXTURE_SIZE as f32) as usize).min(TEXTURE_SIZE - 1),
+                );
+                let draw_color = (sprite.draw_colors >> (color * 4)) & 0xf;
+
+                if draw_color == 0
+                    || !self.depth_buffer.is_visible(x, y, distance)
+                    || self.fog.covers(distance, x, y)
+                {
+                    continue;
+                }
+
+                self.depth_buffer.set(x, y, distance);
+                platform.set_draw_colors(draw_color);
+                platform.pixel(x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::dither;
+use crate::face::Face;
+use crate::vec3::*;
+
+// Shading of a face facing away from the sun, in palette colors.
+const MAX_SHADE: f32 = 2.0;
+
+pub struct Sun {
+    // Normalized direction that sunlight travels in,
+    // the world's y axis points down so light from above has a positive y.
+    // It's replaced every frame while follows_sky is set.
+    pub direction: Vec3<f32>,
+    // Move the sun with the sky's day cycle, set_direction turns this off.
+    pub follows_sky: bool,
+    // Cast a secondary ray towards the sun from each hit to check if it's shadowed.
+    pub shadows: bool,
+    // How far shadow rays travel, shorter ranges are cheaper but miss distant occluders.
+    pub shadow_range: f32,
+}
+
+impl Sun {
+    pub const fn new() -> Self {
+        Self {
+            direction: Vec3::new(0.1489, 0.9429, 0.2978),
+            follows_sky: true,
+            shadows: true,
+            shadow_range: 8.0,
+        }
+    }
+
+    // Light the world from a fixed direction instead of following the sky.
+    pub fn set_direction(&mut self, direction: Vec3<f32>) {
+        self.follows_sky = false;
+        let length =
+            (direction.x * direction.x + direction.y * direction.y + direction.z * direction.z)
+                .sqrt();
+        self.direction = Vec3::new(
+            direction.x / length,
+            direction.y / length,
+            direction.z / length,
+        );
+    }
+
+    // Direction that shadow rays should be cast in.
+    pub fn towards(&self) -> Vec3<f32> {
+        Vec3::new(-self.direction.x, -self.direction.y, -self.direction.z)
+    }
+
+    // Lambert term for a face, 0.0 when facing away from the sun and 1.0 when facing it directly.
+    pub fn lambert(&self, face: Face) -> f32 {
+        let normal = face.normal();
+
+        -(normal.x as f32 * self.direction.x
+            + normal.y as f32 * self.direction.y
+            + normal.z as f32 * self.direction.z)
+            .min(0.0)
+    }
+
+    // Convert an amount of light into the number of palette colors to darken by,
+    // the light is split into bands which can then be dithered between.
+    pub fn shade(&self, light: f32) -> f32 {
+        ((1.0 - light) * MAX_SHADE * dither::SHADE_BANDS).round() / dither::SHADE_BANDS
+    }
+}
+
+impl Default for Sun {
+    fn default() -> Self {
+        Self::new()
+    }
+}
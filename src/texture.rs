@@ -0,0 +1,204 @@
+pub const TEXTURE_SIZE: usize = 8;
+// 2 bits per pixel, so each row of a tile takes 2 bytes.
+pub const TILE_BYTES: usize = TEXTURE_SIZE * TEXTURE_SIZE / 4;
+
+// Tile ids into the atlas:
+pub const STONE: u8 = 0;
+pub const DIRT: u8 = 1;
+pub const GRASS_TOP: u8 = 2;
+pub const GRASS_SIDE: u8 = 3;
+pub const WOOD_SIDE: u8 = 4;
+pub const WOOD_TOP: u8 = 5;
+pub const PLANKS: u8 = 6;
+pub const BEDROCK: u8 = 7;
+pub const GLASS: u8 = 8;
+pub const LEAVES: u8 = 9;
+// Animated tiles are followed by the rest of their frames.
+pub const WATER: u8 = 10;
+pub const MIRROR: u8 = 12;
+pub const TORCH: u8 = 13;
+pub const GLOWSTONE: u8 = 15;
+pub const LAVA: u8 = 16;
+
+const TILE_COUNT: usize = 18;
+
+// 8x8 tiles stored in the same 2bpp format used by WASM-4's blit,
+// the leftmost pixel of each byte is in the highest bits.
+#[rustfmt::skip]
+const ATLAS: [u8; TILE_COUNT * TILE_BYTES] = [
+    // Stone
+    0b00_01_00_00, 0b00_00_10_00,
+    0b01_10_01_00, 0b00_00_00_01,
+    0b00_01_00_00, 0b10_01_00_00,
+    0b00_00_00_01, 0b10_01_00_00,
+    0b10_00_00_00, 0b01_00_00_00,
+    0b01_10_00_00, 0b00_00_01_10,
+    0b00_01_00_00, 0b00_01_10_01,
+    0b00_00_00_00, 0b00_00_01_00,
+    // Dirt
+    0b01_00_10_00, 0b01_00_00_01,
+    0b00_01_00_00, 0b00_01_10_00,
+    0b00_00_01_10, 0b00_00_01_00,
+    0b10_00_00_01, 0b00_00_00_10,
+    0b00_01_00_00, 0b10_01_00_00,
+    0b01_00_00_00, 0b00_00_01_00,
+    0b00_10_01_00, 0b01_00_00_10,
+    0b01_00_00_10, 0b00_01_00_00,
+    // Grass top
+    0b00_01_00_00, 0b01_00_01_00,
+    0b01_00_01_00, 0b00_10_00_01,
+    0b00_01_00_10, 0b00_00_01_00,
+    0b00_00_01_00, 0b01_00_00_10,
+    0b10_00_00_01, 0b00_01_00_00,
+    0b00_01_00_00, 0b10_00_01_00,
+    0b01_00_01_00, 0b01_00_00_01,
+    0b00_10_00_01, 0b00_01_10_00,
+    // Grass side
+    0b00_01_00_00, 0b01_00_01_00,
+    0b01_00_10_01, 0b00_01_00_01,
+    0b10_01_01_11, 0b01_10_00_01,
+    0b01_00_10_11, 0b00_01_11_00,
+    0b01_00_10_00, 0b01_00_00_01,
+    0b00_00_01_10, 0b00_00_01_00,
+    0b10_00_00_01, 0b00_00_00_10,
+    0b00_01_00_00, 0b10_01_00_00,
+    // Wood side
+    0b01_10_01_00, 0b01_10_01_00,
+    0b01_10_01_00, 0b01_10_01_00,
+    0b01_10_01_00, 0b01_11_01_00,
+    0b01_01_01_00, 0b01_10_01_00,
+    0b01_10_01_00, 0b01_10_01_00,
+    0b01_11_01_00, 0b01_10_01_01,
+    0b01_10_01_00, 0b01_10_01_00,
+    0b01_10_01_01, 0b01_10_01_00,
+    // Wood top
+    0b01_01_01_01, 0b01_01_01_01,
+    0b01_10_10_10, 0b10_10_10_01,
+    0b01_10_01_01, 0b01_01_10_01,
+    0b01_10_01_11, 0b11_01_10_01,
+    0b01_10_01_11, 0b11_01_10_01,
+    0b01_10_01_01, 0b01_01_10_01,
+    0b01_10_10_10, 0b10_10_10_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    // Planks
+    0b00_00_00_01, 0b00_00_00_00,
+    0b00_00_00_01, 0b00_00_00_00,
+    0b10_10_10_10, 0b10_10_10_10,
+    0b00_00_00_00, 0b00_00_00_01,
+    0b00_00_00_00, 0b00_00_00_01,
+    0b10_10_10_10, 0b10_10_10_10,
+    0b00_01_00_00, 0b00_00_00_00,
+    0b11_11_11_11, 0b11_11_11_11,
+    // Bedrock
+    0b11_10_11_01, 0b10_01_11_11,
+    0b10_11_01_10, 0b01_11_10_10,
+    0b01_11_11_11, 0b10_11_01_11,
+    0b10_01_10_01, 0b11_11_11_01,
+    0b11_11_01_10, 0b11_10_01_10,
+    0b01_11_10_11, 0b01_11_01_11,
+    0b11_10_11_01, 0b11_10_10_01,
+    0b10_11_01_10, 0b01_11_11_11,
+    // Glass
+    0b01_01_01_01, 0b01_01_01_01,
+    0b01_00_00_00, 0b00_00_10_01,
+    0b01_00_00_00, 0b00_10_00_01,
+    0b01_00_00_00, 0b10_00_00_01,
+    0b01_00_00_10, 0b00_00_00_01,
+    0b01_00_10_00, 0b00_00_00_01,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    // Leaves
+    0b01_10_00_01, 0b01_00_10_01,
+    0b10_01_01_00, 0b10_11_01_00,
+    0b00_01_10_01, 0b00_01_10_01,
+    0b01_00_00_10, 0b01_10_00_00,
+    0b10_00_01_10, 0b00_00_01_10,
+    0b01_10_11_00, 0b01_00_10_01,
+    0b00_01_00_10, 0b01_01_00_10,
+    0b10_01_01_00, 0b00_10_01_00,
+    // Water
+    0b01_01_01_01, 0b01_01_01_01,
+    0b01_10_10_01, 0b01_01_01_01,
+    0b01_01_01_01, 0b10_10_01_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    0b01_01_01_01, 0b01_01_10_10,
+    0b10_01_01_01, 0b01_01_01_01,
+    0b01_01_10_10, 0b01_01_01_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    // Water, second frame
+    0b01_01_01_01, 0b01_01_01_01,
+    0b01_01_01_10, 0b10_01_01_01,
+    0b01_01_01_01, 0b01_01_10_10,
+    0b01_01_01_01, 0b01_01_01_01,
+    0b10_10_01_01, 0b01_01_01_01,
+    0b01_01_10_01, 0b01_01_01_01,
+    0b01_01_01_01, 0b01_10_10_01,
+    0b01_01_01_01, 0b01_01_01_01,
+    // Mirror
+    0b10_01_01_01, 0b01_01_01_10,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b01_00_00_00, 0b00_00_00_01,
+    0b10_01_01_01, 0b01_01_01_10,
+    // Torch
+    0b00_00_00_00, 0b00_00_00_00,
+    0b00_00_00_01, 0b01_00_00_00,
+    0b00_00_01_01, 0b01_01_00_00,
+    0b00_00_01_01, 0b01_01_00_00,
+    0b00_00_00_10, 0b10_00_00_00,
+    0b00_00_00_11, 0b10_00_00_00,
+    0b00_00_00_11, 0b10_00_00_00,
+    0b00_00_00_11, 0b10_00_00_00,
+    // Torch, second frame
+    0b00_00_00_00, 0b00_00_00_00,
+    0b00_00_00_00, 0b01_00_00_00,
+    0b00_00_00_01, 0b01_01_00_00,
+    0b00_00_01_01, 0b01_01_00_00,
+    0b00_00_00_10, 0b10_00_00_00,
+    0b00_00_00_11, 0b10_00_00_00,
+    0b00_00_00_11, 0b10_00_00_00,
+    0b00_00_00_11, 0b10_00_00_00,
+    // Glowstone
+    0b00_01_01_00, 0b00_01_01_00,
+    0b01_00_00_01, 0b01_00_00_01,
+    0b01_00_01_01, 0b01_01_00_01,
+    0b00_01_00_01, 0b01_00_01_00,
+    0b00_01_00_01, 0b01_00_01_00,
+    0b01_00_01_01, 0b01_01_00_01,
+    0b01_00_00_01, 0b01_00_00_01,
+    0b00_01_01_00, 0b00_01_01_00,
+    // Lava
+    0b00_01_01_00, 0b00_00_01_00,
+    0b01_00_00_01, 0b01_01_00_01,
+    0b00_00_00_00, 0b00_01_01_00,
+    0b00_01_01_00, 0b00_00_00_00,
+    0b01_01_00_01, 0b01_00_00_00,
+    0b00_00_00_00, 0b01_01_00_01,
+    0b01_00_00_00, 0b00_01_01_00,
+    0b00_01_01_01, 0b00_00_00_00,
+    // Lava, second frame
+    0b00_01_01_01, 0b00_00_00_00,
+    0b00_01_01_00, 0b00_00_01_00,
+    0b01_00_00_01, 0b01_01_00_01,
+    0b00_00_00_00, 0b00_01_01_00,
+    0b00_01_01_00, 0b00_00_00_00,
+    0b01_01_00_01, 0b01_00_00_00,
+    0b00_00_00_00, 0b01_01_00_01,
+    0b01_00_00_00, 0b00_01_01_00,
+];
+
+// Get the palette color (0-3) of a pixel in a tile.
+pub fn sample(tile: u8, u: usize, v: usize) -> u8 {
+    sample_atlas(&ATLAS, tile, u, v)
+}
+
+// Same as sample, for any atlas of 8x8 tiles in the same format.
+pub fn sample_atlas(atlas: &[u8], tile: u8, u: usize, v: usize) -> u8 {
+    let index = tile as usize * TILE_BYTES + v * 2 + (u >> 2);
+    let shift = (3 - (u & 0b11)) << 1;
+
+    (atlas[index] >> shift) & 0b11
+}
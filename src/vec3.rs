@@ -1,4 +1,4 @@
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct Vec3<T> {
     pub x: T,
     pub y: T,
@@ -16,45 +16,65 @@ impl<T> Vec3<T> {
 }
 
 impl Vec3<f32> {
-    pub fn rotated(&self, rotation: &Vec3<f32>) -> Vec3<f32> {
-        let mut self_rotated = *self;
-        self_rotated.rotate_by(rotation);
-        self_rotated
+    pub fn dot(&self, other: &Vec3<f32>) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
     }
+}
+
+// 3x3 matrix stored as its columns, used for rotations.
+// The columns are where the x, y and z axes end up after the rotation.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    pub x: Vec3<f32>,
+    pub y: Vec3<f32>,
+    pub z: Vec3<f32>,
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3 {
+        x: Vec3::new(1.0, 0.0, 0.0),
+        y: Vec3::new(0.0, 1.0, 0.0),
+        z: Vec3::new(0.0, 0.0, 1.0),
+    };
+
+    // Rotation by roll (z), then pitch (x), then yaw (y), all in radians.
+    // Rolling first keeps it around the forward axis, so it doesn't change where a camera looks.
+    pub fn from_rotation(rotation: &Vec3<f32>) -> Self {
+        let (x_sin, x_cos) = rotation.x.sin_cos();
+        let (y_sin, y_cos) = rotation.y.sin_cos();
+        let (z_sin, z_cos) = rotation.z.sin_cos();
 
-    pub fn rotate_by(&mut self, rotation: &Vec3<f32>) {
-        // let a = self.camera.rotation.z;
-        let b = rotation.y;
-        let c = rotation.x;
-        let b_cos = b.cos();
-        let b_sin = b.sin();
-        let c_cos = c.cos();
-        let c_sin = c.sin();
-
-        // This formula includes all axes, but currently z isn't used:
-        // Vec3::<f32> {
-        //     x: direction.x * (a.cos() * b.cos()) + direction.y * (a.cos() * b.sin() * c.sin() - a.sin() * c.cos()) + direction.z * (a.cos() * b.sin() * c.cos() + a.sin() * c.sin()),
-        //     y: direction.x * (a.sin() * b.cos()) + direction.y * (a.sin() * b.sin() * c.sin() + a.cos() * c.cos()) + direction.z * (a.sin() * b.sin() * c.cos() - a.cos() * c.sin()),
-        //     z: direction.x * (-b.sin()) + direction.y * (b.cos() * c.sin()) + direction.z * (b.cos() * c.cos()),
-        // }
-
-        // This formula skips z:
-        let x = self.x;
-        let y = self.y;
-        let z = self.z;
-
-        self.x = x * b_cos + y * b_sin * c_sin + z * b_sin * c_cos;
-        self.y = y * c_cos + z * -c_sin;
-        self.z = x * -b_sin + y * b_cos * c_sin + z * b_cos * c_cos;
+        // Yaw and pitch:
+        let x = Vec3::new(y_cos, 0.0, -y_sin);
+        let y = Vec3::new(y_sin * x_sin, x_cos, y_cos * x_sin);
+        let z = Vec3::new(y_sin * x_cos, -x_sin, y_cos * x_cos);
+
+        // Then roll, which mixes the x and y axes:
+        Self {
+            x: Vec3::new(
+                x.x * z_cos + y.x * z_sin,
+                x.y * z_cos + y.y * z_sin,
+                x.z * z_cos + y.z * z_sin,
+            ),
+            y: Vec3::new(
+                y.x * z_cos - x.x * z_sin,
+                y.y * z_cos - x.y * z_sin,
+                y.z * z_cos - x.z * z_sin,
+            ),
+            z,
+        }
     }
 
-    pub fn rotate_by_precalculated(&mut self, x_sin: f32, x_cos: f32, y_sin: f32, y_cos: f32) {
-        let x = self.x;
-        let y = self.y;
-        let z = self.z;
+    pub fn transform(&self, v: &Vec3<f32>) -> Vec3<f32> {
+        Vec3::new(
+            self.x.x * v.x + self.y.x * v.y + self.z.x * v.z,
+            self.x.y * v.x + self.y.y * v.y + self.z.y * v.z,
+            self.x.z * v.x + self.y.z * v.y + self.z.z * v.z,
+        )
+    }
 
-        self.x = x * y_cos + y * y_sin * x_sin + z * y_sin * x_cos;
-        self.y = y * x_cos + z * -x_sin;
-        self.z = x * -y_sin + y * y_cos * x_sin + z * y_cos * x_cos;
+    // Undo transform, only valid for rotations since it uses the transpose as the inverse.
+    pub fn inverse_transform(&self, v: &Vec3<f32>) -> Vec3<f32> {
+        Vec3::new(self.x.dot(v), self.y.dot(v), self.z.dot(v))
     }
 }
\ No newline at end of file
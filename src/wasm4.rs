@@ -3,6 +3,8 @@
 
 #![allow(unused)]
 
+use core::ptr;
+
 // ┌───────────────────────────────────────────────────────────────────────────┐
 // │                                                                           │
 // │ Platform Constants                                                        │
@@ -17,18 +19,18 @@ pub const SCREEN_SIZE: u32 = 160;
 // │                                                                           │
 // └───────────────────────────────────────────────────────────────────────────┘
 
-pub const PALETTE: *mut [u32; 4] = 0x04 as *mut [u32; 4];
-pub const DRAW_COLORS: *mut u16 = 0x14 as *mut u16;
-pub const GAMEPAD1: *const u8 = 0x16 as *const u8;
-pub const GAMEPAD2: *const u8 = 0x17 as *const u8;
-pub const GAMEPAD3: *const u8 = 0x18 as *const u8;
-pub const GAMEPAD4: *const u8 = 0x19 as *const u8;
-pub const MOUSE_X: *const i16 = 0x1a as *const i16;
-pub const MOUSE_Y: *const i16 = 0x1c as *const i16;
-pub const MOUSE_BUTTONS: *const u8 = 0x1e as *const u8;
-pub const SYSTEM_FLAGS: *mut u8 = 0x1f as *mut u8;
-pub const NETPLAY: *const u8 = 0x20 as *const u8;
-pub const FRAMEBUFFER: *mut [u8; 6400] = 0xa0 as *mut [u8; 6400];
+pub const PALETTE: *mut [u32; 4] = ptr::without_provenance_mut(0x04);
+pub const DRAW_COLORS: *mut u16 = ptr::without_provenance_mut(0x14);
+pub const GAMEPAD1: *const u8 = ptr::without_provenance(0x16);
+pub const GAMEPAD2: *const u8 = ptr::without_provenance(0x17);
+pub const GAMEPAD3: *const u8 = ptr::without_provenance(0x18);
+pub const GAMEPAD4: *const u8 = ptr::without_provenance(0x19);
+pub const MOUSE_X: *const i16 = ptr::without_provenance(0x1a);
+pub const MOUSE_Y: *const i16 = ptr::without_provenance(0x1c);
+pub const MOUSE_BUTTONS: *const u8 = ptr::without_provenance(0x1e);
+pub const SYSTEM_FLAGS: *mut u8 = ptr::without_provenance_mut(0x1f);
+pub const NETPLAY: *const u8 = ptr::without_provenance(0x20);
+pub const FRAMEBUFFER: *mut [u8; 6400] = ptr::without_provenance_mut(0xa0);
 
 pub const BUTTON_1: u8 = 1;
 pub const BUTTON_2: u8 = 2;